@@ -0,0 +1,44 @@
+//! Batch benchmark - compares contains_batch_simd with per-item contains
+//!
+//! Run with `--features simd` to measure the lane-parallel path.
+
+use std::time::Instant;
+
+use bloom_filter_rs::PrecisionBloom;
+
+fn main() {
+    let items = 1_000_000;
+    let queries: Vec<usize> = (0..items * 4).collect();
+
+    println!("====================================");
+    println!("  Batch Query Benchmark");
+    println!("====================================\n");
+    println!(
+        "simd feature: {}\n",
+        if cfg!(feature = "simd") { "on" } else { "off" }
+    );
+
+    let mut filter = PrecisionBloom::with_capacity(items, 0.01);
+    for i in 0..items {
+        filter.insert(&i);
+    }
+
+    let start = Instant::now();
+    let scalar: Vec<bool> = queries.iter().map(|i| filter.contains(i)).collect();
+    let scalar_time = start.elapsed();
+
+    let start = Instant::now();
+    let batch = filter.contains_batch_simd(&queries);
+    let batch_time = start.elapsed();
+
+    assert_eq!(scalar, batch);
+
+    println!("Queries: {}", queries.len());
+    println!("Found: {}", batch.iter().filter(|&&found| found).count());
+    println!("Per-item: {:.2?}", scalar_time);
+    println!("Batch:    {:.2?}", batch_time);
+    println!(
+        "Speedup:  {:.2}x",
+        scalar_time.as_secs_f64() / batch_time.as_secs_f64()
+    );
+}
@@ -0,0 +1,50 @@
+//! Optimized benchmark - compares queries on PrecisionBloom and OptimizedBloom
+
+use std::time::Instant;
+
+use bloom_filter_rs::PrecisionBloom;
+
+fn main() {
+    let items = 1_000_000;
+    let queries = items * 4;
+
+    println!("====================================");
+    println!("  Optimized Query Benchmark");
+    println!("====================================\n");
+
+    let mut filter = PrecisionBloom::with_capacity(items, 0.01);
+    for i in 0..items {
+        filter.insert(&i);
+    }
+
+    // Mostly absent queries, as in a typical cache-miss workload
+    let start = Instant::now();
+    let mut found = 0;
+    for i in 0..queries {
+        if filter.contains(&i) {
+            found += 1;
+        }
+    }
+    let original = start.elapsed();
+
+    let optimized = filter.into_optimized();
+    let start = Instant::now();
+    let mut optimized_found = 0;
+    for i in 0..queries {
+        if optimized.contains(&i) {
+            optimized_found += 1;
+        }
+    }
+    let fast = start.elapsed();
+
+    assert_eq!(found, optimized_found);
+
+    println!("Queries: {}", queries);
+    println!("Found: {}", found);
+    println!("PrecisionBloom: {:.2?}", original);
+    println!("OptimizedBloom: {:.2?}", fast);
+    println!(
+        "Speedup:        {:.2}x",
+        original.as_secs_f64() / fast.as_secs_f64()
+    );
+}
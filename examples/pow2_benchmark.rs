@@ -0,0 +1,42 @@
+//! Power-of-two benchmark - compares masked and modulo index reduction
+
+use std::time::Instant;
+
+use bloom_filter_rs::{BloomParameters, PrecisionBloom};
+
+fn run(label: &str, params: BloomParameters, items: usize) {
+    let mut filter = PrecisionBloom::new(params);
+
+    let start = Instant::now();
+    for i in 0..items {
+        filter.insert(&i);
+    }
+    let mut found = 0;
+    for i in 0..items * 2 {
+        if filter.contains(&i) {
+            found += 1;
+        }
+    }
+    let elapsed = start.elapsed();
+
+    println!("{}:", label);
+    println!("  Bits: {}", filter.num_bits());
+    println!("  Hash functions: {}", filter.num_hashes());
+    println!("  Found: {}", found);
+    println!("  Time: {:.2?}", elapsed);
+}
+
+fn main() {
+    let items = 1_000_000;
+    let fpr = 0.01;
+
+    println!("====================================");
+    println!("  Power-of-Two Index Benchmark");
+    println!("====================================\n");
+
+    let modulo = BloomParameters::from_item_count(items, fpr);
+    let masked = BloomParameters::from_item_count_pow2(items, fpr);
+
+    run("Modulo", modulo, items);
+    run("Bitmask", masked, items);
+}
@@ -0,0 +1,61 @@
+//! Single-hash benchmark - compares the k=1 fast path with general double hashing
+
+use std::time::Instant;
+
+use bloom_filter_rs::{BitArray, BloomParameters, HashStrategy, PrecisionBloom};
+
+fn main() {
+    let items = 1_000_000;
+    let params = BloomParameters::from_bit_count(items, items * 2);
+    assert_eq!(params.num_hashes, 1);
+
+    println!("====================================");
+    println!("  Single-Hash Fast Path Benchmark");
+    println!("====================================\n");
+
+    // Fast path: PrecisionBloom detects k=1 and computes one index directly
+    let mut filter = PrecisionBloom::new(params);
+    let start = Instant::now();
+    for i in 0..items {
+        filter.insert(&i);
+    }
+    let mut found = 0;
+    for i in 0..items * 2 {
+        if filter.contains(&i) {
+            found += 1;
+        }
+    }
+    let fast = start.elapsed();
+
+    // General path: both base hashes and an index Vec per item
+    let strategy = HashStrategy::new(params.num_hashes, params.num_bits);
+    let mut bits = BitArray::new(params.num_bits);
+    let start = Instant::now();
+    for i in 0..items {
+        for index in strategy.hash_indices(&i) {
+            bits.set(index);
+        }
+    }
+    let mut general_found = 0;
+    for i in 0..items * 2 {
+        if strategy
+            .hash_indices(&i)
+            .iter()
+            .all(|&index| bits.get(index))
+        {
+            general_found += 1;
+        }
+    }
+    let general = start.elapsed();
+
+    assert_eq!(found, general_found);
+
+    println!("Bits: {}", params.num_bits);
+    println!("Found: {}", found);
+    println!("Fast path:    {:.2?}", fast);
+    println!("General path: {:.2?}", general);
+    println!(
+        "Speedup:      {:.2}x",
+        general.as_secs_f64() / fast.as_secs_f64()
+    );
+}
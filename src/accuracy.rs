@@ -3,17 +3,76 @@
 //! This module provides tools to track and monitor the actual false positive
 //! rate of a bloom filter compared to its theoretical rate.
 
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::filter::PrecisionBloom;
 use crate::params::BloomParameters;
 
+/// Number of recent `insert_if_absent` outcomes used for the novelty rate.
+const NOVELTY_WINDOW: usize = 1024;
+
+/// A point-in-time sample of the filter's false positive rate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FprSample {
+    /// Number of items inserted when the sample was taken
+    pub items_inserted: usize,
+    /// Theoretical false positive rate at that fill level
+    pub theoretical_fpr: f64,
+}
+
+/// Query counter that can be incremented through a shared reference.
+///
+/// Queries take `&self`, so the count lives in an atomic. Relaxed ordering
+/// is enough since the value is only a statistic.
+#[derive(Debug, Default)]
+struct QueryCounter(AtomicUsize);
+
+impl QueryCounter {
+    fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn set(&mut self, value: usize) {
+        *self.0.get_mut() = value;
+    }
+
+    fn increment(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl Clone for QueryCounter {
+    fn clone(&self) -> Self {
+        Self(AtomicUsize::new(self.get()))
+    }
+}
+
+impl PartialEq for QueryCounter {
+    fn eq(&self, other: &Self) -> bool {
+        self.get() == other.get()
+    }
+}
+
 /// Tracks the accuracy and performance of a bloom filter.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct AccuracyTracker {
     /// Parameters of the bloom filter
     params: BloomParameters,
     /// Number of items actually inserted
     items_inserted: usize,
     /// Number of queries performed
-    queries_performed: usize,
+    queries_performed: QueryCounter,
+    /// Recent `insert_if_absent` outcomes (`true` = item was new)
+    recent_outcomes: VecDeque<bool>,
+    /// Number of `true` entries in `recent_outcomes`
+    recent_novel: usize,
+    /// Most recent FPR samples, oldest first. Holds up to twice
+    /// `history_capacity` entries so the oldest half can be dropped in one
+    /// drain; only the last `history_capacity` are exposed.
+    history: Vec<FprSample>,
+    /// Maximum number of samples kept (0 disables history)
+    history_capacity: usize,
 }
 
 impl AccuracyTracker {
@@ -22,7 +81,11 @@ impl AccuracyTracker {
         Self {
             params,
             items_inserted: 0,
-            queries_performed: 0,
+            queries_performed: QueryCounter::default(),
+            recent_outcomes: VecDeque::with_capacity(NOVELTY_WINDOW),
+            recent_novel: 0,
+            history: Vec::new(),
+            history_capacity: 0,
         }
     }
 
@@ -32,8 +95,71 @@ impl AccuracyTracker {
     }
 
     /// Record a query operation.
-    pub fn record_query(&mut self) {
-        self.queries_performed += 1;
+    ///
+    /// Takes `&self` so read-only queries can be counted.
+    pub fn record_query(&self) {
+        self.queries_performed.increment();
+    }
+
+    /// Record the outcome of an `insert_if_absent` call.
+    ///
+    /// Only the most recent outcomes are kept, forming a sliding window.
+    pub fn record_novelty(&mut self, was_new: bool) {
+        if self.recent_outcomes.len() == NOVELTY_WINDOW
+            && self.recent_outcomes.pop_front() == Some(true)
+        {
+            self.recent_novel -= 1;
+        }
+
+        self.recent_outcomes.push_back(was_new);
+        if was_new {
+            self.recent_novel += 1;
+        }
+    }
+
+    /// Get the fraction of recent `insert_if_absent` calls that saw a new item.
+    ///
+    /// Values near 0.0 mean the input stream is mostly duplicates. Returns 1.0
+    /// when no outcomes have been recorded yet.
+    pub fn recent_novelty_rate(&self) -> f64 {
+        if self.recent_outcomes.is_empty() {
+            return 1.0;
+        }
+        self.recent_novel as f64 / self.recent_outcomes.len() as f64
+    }
+
+    /// Set how many FPR samples to keep, discarding the oldest beyond that.
+    ///
+    /// A capacity of 0 disables history.
+    pub fn set_history_capacity(&mut self, capacity: usize) {
+        let kept = self.history.len().min(self.history_capacity).min(capacity);
+        self.history.drain(..self.history.len() - kept);
+        self.history_capacity = capacity;
+    }
+
+    /// Record the current fill level and FPR as a history sample.
+    ///
+    /// Does nothing if history is disabled. Once full, the oldest sample is
+    /// dropped to make room.
+    pub fn snapshot(&mut self) {
+        if self.history_capacity == 0 {
+            return;
+        }
+        if self.history.len() >= 2 * self.history_capacity {
+            self.history
+                .drain(..self.history.len() - self.history_capacity);
+        }
+
+        self.history.push(FprSample {
+            items_inserted: self.items_inserted,
+            theoretical_fpr: self.actual_fpr(),
+        });
+    }
+
+    /// Get the recorded FPR samples, oldest first.
+    pub fn fpr_history(&self) -> &[FprSample] {
+        let start = self.history.len().saturating_sub(self.history_capacity);
+        &self.history[start..]
     }
 
     /// Get the number of items inserted.
@@ -43,7 +169,7 @@ impl AccuracyTracker {
 
     /// Get the number of queries performed.
     pub fn queries_performed(&self) -> usize {
-        self.queries_performed
+        self.queries_performed.get()
     }
 
     /// Get the theoretical false positive rate based on parameters.
@@ -81,11 +207,8 @@ impl AccuracyTracker {
     ///
     /// Returns 0 if not overfilled.
     pub fn overfill_amount(&self) -> usize {
-        if self.items_inserted > self.params.expected_items {
-            self.items_inserted - self.params.expected_items
-        } else {
-            0
-        }
+        self.items_inserted
+            .saturating_sub(self.params.expected_items)
     }
 
     /// Get a status summary as a string.
@@ -95,15 +218,246 @@ impl AccuracyTracker {
             self.items_inserted,
             self.params.expected_items,
             self.fill_ratio() * 100.0,
-            self.queries_performed,
+            self.queries_performed.get(),
             self.theoretical_fpr() * 100.0,
             self.actual_fpr() * 100.0
         )
     }
 
+    /// Restore the insert and query counts, e.g. after deserializing a filter.
+    pub fn set_counts(&mut self, items_inserted: usize, queries_performed: usize) {
+        self.items_inserted = items_inserted;
+        self.queries_performed.set(queries_performed);
+    }
+
     /// Reset the tracker (useful for reusing a filter).
     pub fn reset(&mut self) {
         self.items_inserted = 0;
-        self.queries_performed = 0;
+        self.queries_performed.set(0);
+        self.recent_outcomes.clear();
+        self.recent_novel = 0;
+        self.history.clear();
+    }
+}
+
+/// Result of validating a filter against a known set of inserted items.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// Number of items checked
+    pub items_checked: usize,
+    /// Number of items the filter reported as absent (must be zero)
+    pub false_negatives: usize,
+}
+
+impl ValidationReport {
+    /// Check if the filter found every item.
+    pub fn is_valid(&self) -> bool {
+        self.false_negatives == 0
+    }
+}
+
+/// Point-in-time metrics of a filter, for export to monitoring systems.
+///
+/// The machine-readable companion to [`PrecisionBloom::status`](crate::PrecisionBloom::status).
+/// With the `serde` feature enabled it implements `Serialize`, with field
+/// names as declared here.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BloomMetrics {
+    /// Number of items inserted, as tracked by the filter
+    pub items_inserted: usize,
+    /// Number of bits in the filter
+    pub num_bits: usize,
+    /// Number of hash functions
+    pub num_hashes: usize,
+    /// Proportion of bits set, from 0.0 to 1.0
+    pub saturation_ratio: f64,
+    /// False positive rate the filter was sized for
+    pub theoretical_fpr: f64,
+    /// Theoretical false positive rate at the current fill level
+    pub actual_fpr: f64,
+    /// Heap memory used by the bit array, in bytes
+    pub memory_bytes: usize,
+    /// Whether more items were inserted than the filter was sized for
+    pub is_overfilled: bool,
+}
+
+/// Combined metrics for a set of filters, such as the shards of a dataset.
+///
+/// Produced by [`aggregate_stats`]. With the `serde` feature enabled it
+/// implements `Serialize`, with field names as declared here.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct AggregateStats {
+    /// Number of filters aggregated
+    pub num_filters: usize,
+    /// Items inserted across all filters
+    pub total_items: usize,
+    /// Queries counted across all filters
+    pub total_queries: usize,
+    /// Heap memory used by all bit arrays, in bytes
+    pub total_memory_bytes: usize,
+    /// Mean proportion of bits set
+    pub mean_saturation: f64,
+    /// False positive rate of a query routed to one uniformly chosen filter,
+    /// the mean of the filters' current rates
+    pub routed_fpr: f64,
+    /// False positive rate of a query checked against every filter
+    pub broadcast_fpr: f64,
+    /// Index of the filter with the highest saturation, if any
+    pub fullest: Option<usize>,
+}
+
+/// Combine the metrics of many filters into one report.
+///
+/// The fleet-level companion to [`PrecisionBloom::metrics`]. Counts and memory
+/// are summed and saturation averaged. For sharded data, `routed_fpr` applies
+/// when each query goes to a single shard and `broadcast_fpr`,
+/// `1 - Π(1 - fpr_i)`, when it is checked against all of them.
+///
+/// # Example
+/// ```
+/// use bloom_filter_rs::{aggregate_stats, PrecisionBloom};
+///
+/// let mut shards = vec![PrecisionBloom::with_capacity(100, 0.01); 2];
+/// shards[1].insert(&"item");
+///
+/// let stats = aggregate_stats(&shards);
+/// assert_eq!(stats.total_items, 1);
+/// assert_eq!(stats.fullest, Some(1));
+/// ```
+pub fn aggregate_stats(filters: &[PrecisionBloom]) -> AggregateStats {
+    let mut stats = AggregateStats {
+        num_filters: filters.len(),
+        total_items: 0,
+        total_queries: 0,
+        total_memory_bytes: 0,
+        mean_saturation: 0.0,
+        routed_fpr: 0.0,
+        broadcast_fpr: 0.0,
+        fullest: None,
+    };
+    if filters.is_empty() {
+        return stats;
+    }
+
+    let mut miss_all = 1.0;
+    let mut fullest_saturation = f64::NEG_INFINITY;
+    for (index, filter) in filters.iter().enumerate() {
+        let saturation = filter.saturation();
+        let fpr = filter.actual_false_positive_rate();
+
+        stats.total_items += filter.len();
+        stats.total_queries += filter.queries_performed();
+        stats.total_memory_bytes += filter.memory_usage();
+        stats.mean_saturation += saturation;
+        stats.routed_fpr += fpr;
+        miss_all *= 1.0 - fpr;
+
+        if saturation > fullest_saturation {
+            fullest_saturation = saturation;
+            stats.fullest = Some(index);
+        }
+    }
+
+    let n = filters.len() as f64;
+    stats.mean_saturation /= n;
+    stats.routed_fpr /= n;
+    stats.broadcast_fpr = 1.0 - miss_all;
+    stats
+}
+
+/// Number of absent-item probes needed to estimate a false positive rate.
+///
+/// Uses the normal approximation to the binomial: estimating a rate `p`
+/// within a relative error `e` at two-sided confidence `c` needs
+/// `n = z² (1 - p) / (e² p)` probes, where `z` is the standard normal
+/// quantile at `1 - (1 - c) / 2`.
+///
+/// # Arguments
+/// * `target_fpr` - Expected false positive rate (between 0 and 1)
+/// * `relative_error` - Acceptable error as a fraction of the rate (e.g. 0.1 for ±10%)
+/// * `confidence` - Confidence level (between 0 and 1, e.g. 0.95)
+///
+/// # Example
+/// ```
+/// use bloom_filter_rs::fpr_validation_sample_size;
+///
+/// // Probes needed to measure a 1% rate within ±10% at 95% confidence
+/// assert_eq!(fpr_validation_sample_size(0.01, 0.1, 0.95), 38_031);
+/// ```
+///
+/// # Panics
+/// Panics if any argument is outside its valid range.
+pub fn fpr_validation_sample_size(target_fpr: f64, relative_error: f64, confidence: f64) -> usize {
+    assert!(
+        target_fpr > 0.0 && target_fpr < 1.0,
+        "target_fpr must be between 0 and 1"
+    );
+    assert!(
+        relative_error > 0.0,
+        "relative_error must be greater than 0"
+    );
+    assert!(
+        confidence > 0.0 && confidence < 1.0,
+        "confidence must be between 0 and 1"
+    );
+
+    let z = inverse_normal_cdf(1.0 - (1.0 - confidence) / 2.0);
+    let n = z * z * (1.0 - target_fpr) / (relative_error * relative_error * target_fpr);
+    n.ceil() as usize
+}
+
+/// Quantile function of the standard normal distribution.
+///
+/// Acklam's rational approximation, accurate to a relative error of about
+/// 1.15e-9 over (0, 1).
+fn inverse_normal_cdf(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    const P_LOW: f64 = 0.02425;
+
+    // Rational tail approximation in q = sqrt(-2 ln(tail probability))
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+
+    if p < P_LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - P_LOW {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
     }
 }
@@ -0,0 +1,126 @@
+//! Bloom filter whose entries fade out over time.
+//!
+//! Each call to [`AgingBloom::tick`] clears every set bit with probability
+//! `decay_rate`, so items that are not re-inserted gradually lose their bits
+//! and stop being reported present. This approximates sliding-window
+//! membership without per-item timestamps.
+
+use std::hash::Hash;
+
+use rand::Rng;
+
+use crate::bit_array::BitArray;
+use crate::hash::HashStrategy;
+use crate::params::BloomParameters;
+
+/// An approximate recency set built on a decaying bloom filter.
+///
+/// Frequently inserted items keep their bits set. An item left alone fades
+/// as soon as any of its `num_hashes` bits is cleared, so it survives a tick
+/// with probability `(1 - decay_rate)^num_hashes`. Decay breaks the no-false-negative
+/// guarantee: an item inserted before the last tick may be reported absent.
+///
+/// # Example
+/// ```
+/// use bloom_filter_rs::AgingBloom;
+///
+/// let mut filter = AgingBloom::with_capacity(1000, 0.01, 0.5);
+/// filter.insert(&"hello");
+/// assert!(filter.contains(&"hello"));
+///
+/// let mut rng = rand::thread_rng();
+/// for _ in 0..64 {
+///     filter.tick(&mut rng);
+/// }
+/// assert!(!filter.contains(&"hello"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct AgingBloom {
+    /// Bit array storing the filter state
+    bits: BitArray,
+    /// Hash strategy for generating bit indices
+    hash_strategy: HashStrategy,
+    /// Filter parameters
+    params: BloomParameters,
+    /// Probability that a set bit is cleared on each tick
+    decay_rate: f64,
+}
+
+impl AgingBloom {
+    /// Create a new aging filter with the given parameters and decay rate.
+    ///
+    /// # Panics
+    /// Panics if the parameters are invalid or `decay_rate` is not between 0 and 1.
+    pub fn new(mut params: BloomParameters, decay_rate: f64) -> Self {
+        params.validate().expect("Invalid parameters");
+        params.clamp_hashes();
+        assert!(
+            (0.0..=1.0).contains(&decay_rate),
+            "decay_rate must be between 0 and 1"
+        );
+
+        Self {
+            bits: BitArray::new(params.num_bits),
+            hash_strategy: HashStrategy::new(params.num_hashes, params.num_bits),
+            params,
+            decay_rate,
+        }
+    }
+
+    /// Create a new aging filter sized for the given number of live items and
+    /// false positive rate.
+    pub fn with_capacity(expected_items: usize, false_positive_rate: f64, decay_rate: f64) -> Self {
+        Self::new(
+            BloomParameters::from_item_count(expected_items, false_positive_rate),
+            decay_rate,
+        )
+    }
+
+    /// Insert an item, setting all of its bits.
+    pub fn insert<T: Hash>(&mut self, item: &T) {
+        for index in self.hash_strategy.hash_indices(item) {
+            self.bits.set(index);
+        }
+    }
+
+    /// Check if an item might be in the filter.
+    ///
+    /// May return `false` for an item whose bits have decayed since it was
+    /// last inserted.
+    pub fn contains<T: Hash>(&self, item: &T) -> bool {
+        self.hash_strategy
+            .hash_indices(item)
+            .iter()
+            .all(|&index| self.bits.get(index))
+    }
+
+    /// Age the filter by one step, clearing each set bit with probability
+    /// `decay_rate`.
+    pub fn tick(&mut self, rng: &mut impl Rng) {
+        for index in 0..self.params.num_bits {
+            if self.bits.get(index) && rng.gen_bool(self.decay_rate) {
+                self.bits.unset(index);
+            }
+        }
+    }
+
+    /// Get the probability that a set bit is cleared on each tick.
+    pub fn decay_rate(&self) -> f64 {
+        self.decay_rate
+    }
+
+    /// Get the fraction of bits currently set.
+    pub fn saturation(&self) -> f64 {
+        self.bits.saturation()
+    }
+
+    /// Clear all items from the filter.
+    pub fn clear(&mut self) {
+        self.bits.clear();
+    }
+
+    /// Get the parameters of this filter.
+    pub fn parameters(&self) -> &BloomParameters {
+        &self.params
+    }
+}
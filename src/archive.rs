@@ -0,0 +1,256 @@
+//! Archives storing many named bloom filters in a single file.
+//!
+//! An archive starts with an index table mapping each name to the offset and
+//! length of its entry, followed by the entries themselves in the binary
+//! format produced by [`PrecisionBloom::to_bytes`]. Readers load the index up
+//! front and seek directly to a single entry, so loading one filter does not
+//! require reading the others.
+
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use crate::error::BloomError;
+use crate::filter::PrecisionBloom;
+
+/// Magic bytes identifying an archive.
+const ARCHIVE_MAGIC: &[u8; 4] = b"PBLA";
+
+/// Version of the archive layout.
+const ARCHIVE_VERSION: u8 = 1;
+
+/// Size of the smallest possible index entry: an empty name, offset and length.
+const MIN_INDEX_ENTRY_LEN: u64 = 2 + 8 + 8;
+
+/// Builds an archive of named filters.
+///
+/// # Example
+/// ```
+/// use std::io::Cursor;
+/// use bloom_filter_rs::{BloomArchive, BloomArchiveWriter, PrecisionBloom};
+///
+/// let mut users = PrecisionBloom::with_capacity(100, 0.01);
+/// users.insert(&"alice");
+///
+/// let mut writer = BloomArchiveWriter::new();
+/// writer.add("users", &users);
+///
+/// let mut buffer = Vec::new();
+/// writer.write_to(&mut buffer).unwrap();
+///
+/// let mut archive = BloomArchive::open(Cursor::new(buffer)).unwrap();
+/// let loaded = archive.get("users").unwrap().unwrap();
+/// assert!(loaded.contains(&"alice"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BloomArchiveWriter {
+    /// Named entries in insertion order, already serialized
+    entries: Vec<(String, Vec<u8>)>,
+}
+
+impl BloomArchiveWriter {
+    /// Create an empty archive writer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a named filter to the archive.
+    ///
+    /// # Panics
+    /// Panics if the name is already present or longer than 65535 bytes.
+    pub fn add(&mut self, name: &str, filter: &PrecisionBloom) {
+        assert!(
+            name.len() <= u16::MAX as usize,
+            "archive entry name too long"
+        );
+        assert!(
+            self.entries.iter().all(|(existing, _)| existing != name),
+            "duplicate archive entry name"
+        );
+
+        self.entries.push((name.to_string(), filter.to_bytes()));
+    }
+
+    /// Get the number of entries added so far.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if no entries have been added.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Write the index table followed by every entry.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), BloomError> {
+        // Header: magic, version, entry count
+        let mut index_len = ARCHIVE_MAGIC.len() + 1 + 4;
+        // Each index entry: name length, name, offset, length
+        for (name, _) in &self.entries {
+            index_len += 2 + name.len() + 8 + 8;
+        }
+
+        writer.write_all(ARCHIVE_MAGIC)?;
+        writer.write_all(&[ARCHIVE_VERSION])?;
+        writer.write_all(&(self.entries.len() as u32).to_le_bytes())?;
+
+        let mut offset = index_len as u64;
+        for (name, bytes) in &self.entries {
+            writer.write_all(&(name.len() as u16).to_le_bytes())?;
+            writer.write_all(name.as_bytes())?;
+            writer.write_all(&offset.to_le_bytes())?;
+            writer.write_all(&(bytes.len() as u64).to_le_bytes())?;
+            offset += bytes.len() as u64;
+        }
+
+        for (_, bytes) in &self.entries {
+            writer.write_all(bytes)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Location of a single entry within an archive.
+#[derive(Debug, Clone)]
+struct ArchiveEntry {
+    /// Name the filter was stored under
+    name: String,
+    /// Offset of the entry from the start of the archive
+    offset: u64,
+    /// Length of the serialized entry in bytes
+    len: u64,
+}
+
+/// Reads named filters from an archive written by [`BloomArchiveWriter`].
+#[derive(Debug)]
+pub struct BloomArchive<R> {
+    /// Underlying reader
+    reader: R,
+    /// Position of the archive start within the reader
+    base: u64,
+    /// Length of the archive from its start to the end of the reader
+    len: u64,
+    /// Index table loaded when the archive was opened
+    index: Vec<ArchiveEntry>,
+}
+
+impl<R: Read + Seek> BloomArchive<R> {
+    /// Open an archive, reading only its index table.
+    ///
+    /// The archive is assumed to start at the reader's current position and
+    /// run to the end of the reader.
+    ///
+    /// # Errors
+    /// Returns `BloomError::InvalidFormat` if the header is malformed or the
+    /// index claims more entries than the remaining data could hold.
+    pub fn open(mut reader: R) -> Result<Self, BloomError> {
+        let base = reader.stream_position()?;
+        let len = reader.seek(SeekFrom::End(0))? - base;
+        reader.seek(SeekFrom::Start(base))?;
+
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != ARCHIVE_MAGIC {
+            return Err(BloomError::InvalidFormat(
+                "bad archive magic bytes".to_string(),
+            ));
+        }
+
+        let mut version = [0u8; 1];
+        reader.read_exact(&mut version)?;
+        if version[0] != ARCHIVE_VERSION {
+            return Err(BloomError::InvalidFormat(format!(
+                "unsupported archive version {}",
+                version[0]
+            )));
+        }
+
+        let mut count = [0u8; 4];
+        reader.read_exact(&mut count)?;
+        let count = u32::from_le_bytes(count) as usize;
+
+        // Bound the count by the data actually present before allocating
+        let remaining = len - (ARCHIVE_MAGIC.len() as u64 + 1 + 4).min(len);
+        if count as u64 > remaining / MIN_INDEX_ENTRY_LEN {
+            return Err(BloomError::InvalidFormat(format!(
+                "archive index claims {} entries but only {} bytes follow",
+                count, remaining
+            )));
+        }
+
+        let mut index = Vec::with_capacity(count);
+        for _ in 0..count {
+            let mut name_len = [0u8; 2];
+            reader.read_exact(&mut name_len)?;
+
+            let mut name = vec![0u8; u16::from_le_bytes(name_len) as usize];
+            reader.read_exact(&mut name)?;
+            let name = String::from_utf8(name)
+                .map_err(|_| BloomError::InvalidFormat("entry name is not UTF-8".to_string()))?;
+
+            let mut offset = [0u8; 8];
+            reader.read_exact(&mut offset)?;
+            let mut len = [0u8; 8];
+            reader.read_exact(&mut len)?;
+
+            index.push(ArchiveEntry {
+                name,
+                offset: u64::from_le_bytes(offset),
+                len: u64::from_le_bytes(len),
+            });
+        }
+
+        Ok(Self {
+            reader,
+            base,
+            len,
+            index,
+        })
+    }
+
+    /// Get the names of all entries, in the order they were written.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.index.iter().map(|entry| entry.name.as_str())
+    }
+
+    /// Get the number of entries in the archive.
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Check if the archive has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Load a single filter by name.
+    ///
+    /// Seeks directly to the entry; other entries are not read.
+    /// Returns `Ok(None)` if no entry has the given name.
+    ///
+    /// # Errors
+    /// Returns `BloomError::InvalidFormat` if the entry extends past the end
+    /// of the archive, or if its bytes are not a valid filter.
+    pub fn get(&mut self, name: &str) -> Result<Option<PrecisionBloom>, BloomError> {
+        let Some(entry) = self.index.iter().find(|entry| entry.name == name) else {
+            return Ok(None);
+        };
+
+        let in_bounds = entry
+            .offset
+            .checked_add(entry.len)
+            .is_some_and(|end| end <= self.len);
+        if !in_bounds {
+            return Err(BloomError::InvalidFormat(format!(
+                "archive entry {:?} extends past the end of the archive",
+                entry.name
+            )));
+        }
+
+        self.reader
+            .seek(SeekFrom::Start(self.base + entry.offset))?;
+        let mut bytes = vec![0u8; entry.len as usize];
+        self.reader.read_exact(&mut bytes)?;
+
+        PrecisionBloom::from_bytes(&bytes).map(Some)
+    }
+}
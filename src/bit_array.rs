@@ -3,6 +3,11 @@
 //! This module provides a simple, correct bit array implementation
 //! using a vector of 64-bit integers.
 
+use std::hint::black_box;
+
+/// Number of u64 words in a typical 4 KiB memory page.
+const WORDS_PER_PAGE: usize = 4096 / 8;
+
 /// A bit array for storing bloom filter state.
 ///
 /// Internally uses a `Vec<u64>` where each u64 stores 64 bits.
@@ -13,6 +18,8 @@ pub struct BitArray {
     words: Vec<u64>,
     /// Total number of bits this array can hold
     capacity: usize,
+    /// Number of set bits, kept current by every mutation
+    ones: usize,
 }
 
 impl BitArray {
@@ -28,13 +35,13 @@ impl BitArray {
     pub fn new(capacity: usize) -> Self {
         assert!(capacity > 0, "capacity must be greater than 0");
 
-        // Calculate how many u64 words we need
-        // We need to round up: (capacity + 63) / 64
-        let num_words = (capacity + 63) / 64;
+        // Calculate how many u64 words we need, rounding up
+        let num_words = capacity.div_ceil(64);
 
         Self {
             words: vec![0u64; num_words],
             capacity,
+            ones: 0,
         }
     }
 
@@ -44,6 +51,15 @@ impl BitArray {
         self.capacity
     }
 
+    /// Map a bit index to its storage location.
+    ///
+    /// Returns `(word_index, bit_within_word)`, where bit 0 of a word is its
+    /// least significant bit.
+    #[inline]
+    pub fn locate(index: usize) -> (usize, usize) {
+        (index / 64, index % 64)
+    }
+
     /// Set the bit at the given index to 1.
     ///
     /// # Arguments
@@ -53,11 +69,30 @@ impl BitArray {
         assert!(index < self.capacity, "index out of bounds");
 
         // Determine which word and which bit within that word
-        let word_index = index / 64;
-        let bit_index = index % 64;
+        let (word_index, bit_index) = Self::locate(index);
+
+        // Set the bit using bitwise OR, counting it if it was unset
+        let mask = 1u64 << bit_index;
+        if self.words[word_index] & mask == 0 {
+            self.words[word_index] |= mask;
+            self.ones += 1;
+        }
+    }
+
+    /// Set the bit at the given index to 0.
+    ///
+    /// # Arguments
+    /// * `index` - The bit index to unset (0-indexed)
+    #[inline]
+    pub fn unset(&mut self, index: usize) {
+        assert!(index < self.capacity, "index out of bounds");
 
-        // Set the bit using bitwise OR
-        self.words[word_index] |= 1u64 << bit_index;
+        let (word_index, bit_index) = Self::locate(index);
+        let mask = 1u64 << bit_index;
+        if self.words[word_index] & mask != 0 {
+            self.words[word_index] &= !mask;
+            self.ones -= 1;
+        }
     }
 
     /// Get the value of the bit at the given index.
@@ -68,8 +103,7 @@ impl BitArray {
         assert!(index < self.capacity, "index out of bounds");
 
         // Determine which word and which bit within that word
-        let word_index = index / 64;
-        let bit_index = index % 64;
+        let (word_index, bit_index) = Self::locate(index);
 
         // Check if the bit is set using bitwise AND
         (self.words[word_index] & (1u64 << bit_index)) != 0
@@ -78,11 +112,66 @@ impl BitArray {
     /// Clear all bits in the array (set to 0).
     pub fn clear(&mut self) {
         self.words.fill(0);
+        self.ones = 0;
+    }
+
+    /// Set every bit that is set in `other` (bitwise OR).
+    ///
+    /// # Panics
+    /// Panics if the arrays have different capacities
+    pub fn or_with(&mut self, other: &BitArray) {
+        self.combine_with(other, |a, b| a | b);
+    }
+
+    /// Keep only bits that are also set in `other` (bitwise AND).
+    ///
+    /// # Panics
+    /// Panics if the arrays have different capacities
+    pub fn and_with(&mut self, other: &BitArray) {
+        self.combine_with(other, |a, b| a & b);
+    }
+
+    /// Flip every bit that is set in `other` (bitwise XOR).
+    ///
+    /// # Panics
+    /// Panics if the arrays have different capacities
+    pub fn xor_with(&mut self, other: &BitArray) {
+        self.combine_with(other, |a, b| a ^ b);
+    }
+
+    /// Combine word by word with another array, recounting set bits in the
+    /// same pass.
+    fn combine_with(&mut self, other: &BitArray, op: impl Fn(u64, u64) -> u64) {
+        assert_eq!(
+            self.capacity, other.capacity,
+            "bit arrays must have the same capacity"
+        );
+
+        let mut ones = 0;
+        for (word, &other_word) in self.words.iter_mut().zip(&other.words) {
+            *word = op(*word, other_word);
+            ones += word.count_ones() as usize;
+        }
+        self.ones = ones;
+    }
+
+    /// Touch every memory page of the array so the OS commits it up front.
+    ///
+    /// Large zeroed allocations are often backed lazily, so the first write to
+    /// each page takes a fault. This rewrites one word per page with its
+    /// current value, leaving the contents unchanged.
+    pub fn prefault(&mut self) {
+        for index in (0..self.words.len()).step_by(WORDS_PER_PAGE) {
+            self.words[index] = black_box(self.words[index]);
+        }
     }
 
     /// Count the number of set bits (1s) in the array.
+    ///
+    /// The count is maintained by every mutation, so this is O(1).
+    #[inline]
     pub fn count_ones(&self) -> usize {
-        self.words.iter().map(|word| word.count_ones() as usize).sum()
+        self.ones
     }
 
     /// Returns saturation ratio between 0.0 (empty) and 1.0 (completely full).
@@ -90,6 +179,78 @@ impl BitArray {
         self.count_ones() as f64 / self.capacity as f64
     }
 
+    /// Compute the normalized Shannon entropy of set-bit positions.
+    ///
+    /// The array is split into `regions` equal runs of words and the share of
+    /// set bits falling into each region forms the distribution. The result is
+    /// normalized to `[0, 1]`: values near 1.0 mean set bits are spread evenly,
+    /// low values mean they are clustered. Returns 0.0 if no bits are set.
+    ///
+    /// # Panics
+    /// Panics if regions is 0
+    pub fn region_entropy(&self, regions: usize) -> f64 {
+        assert!(regions > 0, "regions must be greater than 0");
+
+        let regions = regions.min(self.words.len());
+        let total = self.count_ones();
+        if total == 0 || regions < 2 {
+            return 0.0;
+        }
+
+        let mut entropy = 0.0;
+        for region in 0..regions {
+            let start = region * self.words.len() / regions;
+            let end = (region + 1) * self.words.len() / regions;
+            let ones: usize = self.words[start..end]
+                .iter()
+                .map(|word| word.count_ones() as usize)
+                .sum();
+
+            if ones > 0 {
+                let p = ones as f64 / total as f64;
+                entropy -= p * p.ln();
+            }
+        }
+
+        entropy / (regions as f64).ln()
+    }
+
+    /// Find the longest run of consecutive unset bits.
+    ///
+    /// Returns the start index and length of the run; the earliest run wins
+    /// ties. Returns `(0, 0)` if every bit is set.
+    pub fn longest_zero_run(&self) -> (usize, usize) {
+        let mut longest = (0, 0);
+        let mut run_start = 0;
+        let mut index = 0;
+
+        while index < self.capacity {
+            let (word_index, bit_index) = Self::locate(index);
+            let word = self.words[word_index] >> bit_index;
+
+            // The rest of this word is unset, so skip it whole
+            if word == 0 {
+                index = ((word_index + 1) * 64).min(self.capacity);
+                continue;
+            }
+
+            let one = index + word.trailing_zeros() as usize;
+            if one >= self.capacity {
+                break;
+            }
+            if one - run_start > longest.1 {
+                longest = (run_start, one - run_start);
+            }
+            run_start = one + 1;
+            index = one + 1;
+        }
+
+        if self.capacity - run_start > longest.1 {
+            longest = (run_start, self.capacity - run_start);
+        }
+        longest
+    }
+
     /// Get a reference to the internal word array.
     ///
     /// This can be useful for serialization or inspection.
@@ -99,12 +260,17 @@ impl BitArray {
 
     /// Create a BitArray from a vector of words and capacity.
     pub fn from_words(words: Vec<u64>, capacity: usize) -> Self {
-        let required_words = (capacity + 63) / 64;
+        let required_words = capacity.div_ceil(64);
         assert!(
             words.len() >= required_words,
             "words vector too small for capacity"
         );
 
-        Self { words, capacity }
+        let ones = words.iter().map(|word| word.count_ones() as usize).sum();
+        Self {
+            words,
+            capacity,
+            ones,
+        }
     }
 }
@@ -0,0 +1,137 @@
+//! Bloom filter backed by two independent hash backends.
+//!
+//! Each half keeps its own bit array addressed by a different pair of hash
+//! functions. An item is reported present only if both halves agree, so a
+//! false positive requires two independent collisions.
+
+use std::hash::Hash;
+
+use crate::bit_array::BitArray;
+use crate::hash::{HashBackend, HashStrategy};
+use crate::params::BloomParameters;
+
+/// A "belt and suspenders" bloom filter combining two hash backends.
+///
+/// Both halves share the same [`BloomParameters`], so the combined false
+/// positive rate is roughly the square of a single half's rate, at the cost
+/// of twice the memory and hashing work.
+///
+/// # Example
+/// ```
+/// use bloom_filter_rs::DualBackendBloom;
+///
+/// let mut filter = DualBackendBloom::with_capacity(1000, 0.01);
+/// filter.insert(&"hello");
+///
+/// assert!(filter.contains(&"hello"));
+/// assert!(!filter.contains(&"world"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct DualBackendBloom {
+    /// Bits addressed by the primary backend
+    primary_bits: BitArray,
+    /// Bits addressed by the secondary backend
+    secondary_bits: BitArray,
+    /// Hash strategy for the primary half
+    primary: HashStrategy,
+    /// Hash strategy for the secondary half
+    secondary: HashStrategy,
+    /// Parameters of each half
+    params: BloomParameters,
+    /// Number of items inserted
+    items_inserted: usize,
+}
+
+impl DualBackendBloom {
+    /// Create a new dual-backend filter with the given parameters for each half.
+    pub fn new(mut params: BloomParameters) -> Self {
+        params.validate().expect("Invalid parameters");
+        params.clamp_hashes();
+
+        Self {
+            primary_bits: BitArray::new(params.num_bits),
+            secondary_bits: BitArray::new(params.num_bits),
+            primary: HashStrategy::with_backend(
+                params.num_hashes,
+                params.num_bits,
+                HashBackend::AhashSeahash,
+            ),
+            secondary: HashStrategy::with_backend(
+                params.num_hashes,
+                params.num_bits,
+                HashBackend::Seahash,
+            ),
+            params,
+            items_inserted: 0,
+        }
+    }
+
+    /// Create a new dual-backend filter where each half is sized for the given
+    /// number of items and false positive rate.
+    pub fn with_capacity(expected_items: usize, false_positive_rate: f64) -> Self {
+        Self::new(BloomParameters::from_item_count(
+            expected_items,
+            false_positive_rate,
+        ))
+    }
+
+    /// Insert an item into both halves.
+    pub fn insert<T: Hash>(&mut self, item: &T) {
+        self.items_inserted += 1;
+
+        for index in self.primary.hash_indices(item) {
+            self.primary_bits.set(index);
+        }
+        for index in self.secondary.hash_indices(item) {
+            self.secondary_bits.set(index);
+        }
+    }
+
+    /// Check if an item might be in the filter.
+    ///
+    /// Returns `true` only if both halves report the item present.
+    pub fn contains<T: Hash>(&self, item: &T) -> bool {
+        self.primary
+            .hash_indices(item)
+            .iter()
+            .all(|&index| self.primary_bits.get(index))
+            && self
+                .secondary
+                .hash_indices(item)
+                .iter()
+                .all(|&index| self.secondary_bits.get(index))
+    }
+
+    /// Clear all items from the filter.
+    pub fn clear(&mut self) {
+        self.primary_bits.clear();
+        self.secondary_bits.clear();
+        self.items_inserted = 0;
+    }
+
+    /// Get the number of items inserted into the filter.
+    pub fn len(&self) -> usize {
+        self.items_inserted
+    }
+
+    /// Check if the filter is empty (no items inserted).
+    pub fn is_empty(&self) -> bool {
+        self.items_inserted == 0
+    }
+
+    /// Get the parameters of each half.
+    pub fn parameters(&self) -> &BloomParameters {
+        &self.params
+    }
+
+    /// Get the theoretical false positive rate at the current fill level.
+    ///
+    /// Both halves must collide independently, so this is the square of a
+    /// single half's rate.
+    pub fn actual_false_positive_rate(&self) -> f64 {
+        if self.items_inserted == 0 {
+            return 0.0;
+        }
+        self.params.actual_fpr(self.items_inserted).powi(2)
+    }
+}
@@ -0,0 +1,65 @@
+//! Error types for bloom filter operations.
+
+use std::fmt;
+use std::io;
+
+/// Errors that can occur when serializing or loading bloom filters.
+#[derive(Debug)]
+pub enum BloomError {
+    /// An I/O error occurred while reading or writing
+    Io(io::Error),
+    /// The input is not a valid serialized filter
+    InvalidFormat(String),
+    /// An item could not be serialized for hashing
+    Serialization(String),
+    /// An insert was refused because the filter is at capacity
+    CapacityExceeded {
+        /// The filter's expected number of items
+        capacity: usize,
+    },
+    /// Two filters cannot be combined because items map to different bits
+    IncompatibleFilters,
+    /// The stored checksum does not match the data, which has been corrupted
+    ChecksumMismatch {
+        /// Checksum stored with the data
+        stored: u32,
+        /// Checksum computed over the data as read
+        computed: u32,
+    },
+}
+
+impl fmt::Display for BloomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BloomError::Io(err) => write!(f, "I/O error: {}", err),
+            BloomError::InvalidFormat(msg) => write!(f, "invalid format: {}", msg),
+            BloomError::Serialization(msg) => write!(f, "serialization error: {}", msg),
+            BloomError::CapacityExceeded { capacity } => {
+                write!(f, "filter is at capacity ({} items)", capacity)
+            }
+            BloomError::IncompatibleFilters => {
+                write!(f, "filters have incompatible parameters or hash backends")
+            }
+            BloomError::ChecksumMismatch { stored, computed } => write!(
+                f,
+                "checksum mismatch: stored {:08x}, computed {:08x}",
+                stored, computed
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BloomError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BloomError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for BloomError {
+    fn from(err: io::Error) -> Self {
+        BloomError::Io(err)
+    }
+}
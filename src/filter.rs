@@ -1,48 +1,290 @@
 //! Core bloom filter implementation.
 
-use std::hash::Hash;
-
-use crate::accuracy::AccuracyTracker;
+use std::collections::{BTreeSet, HashSet};
+use std::fs::OpenOptions;
+use std::hash::{Hash, Hasher};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+
+#[cfg(feature = "rand")]
+use rand::Rng;
+
+use crate::accuracy::{AccuracyTracker, BloomMetrics, FprSample, ValidationReport};
 use crate::bit_array::BitArray;
-use crate::hash::HashStrategy;
+use crate::error::BloomError;
+use crate::hash::{shard_of, DoubleHashScheme, HashBackend, HashStrategy};
+use crate::key::{BloomKey, BloomKeyHasher};
+use crate::optimized::OptimizedBloom;
 use crate::params::BloomParameters;
 
+/// Number of regions used when measuring the entropy of set bits.
+const ENTROPY_REGIONS: usize = 64;
+
+/// Number of words shown at each end of the array by `debug_dump`.
+const DUMP_WORDS: usize = 4;
+
+/// Maximum number of rebuilds attempted by `fit_for_sets`.
+const MAX_FIT_ROUNDS: usize = 24;
+
+/// Estimated per-entry overhead of a `HashSet`, in bytes.
+///
+/// Covers the stored hash metadata and the slack from the table's maximum
+/// load factor, on top of the item itself.
+const HASHSET_ENTRY_OVERHEAD: usize = 16;
+
+/// Magic bytes identifying a serialized filter.
+const MAGIC: &[u8; 4] = b"PBLM";
+
+/// Version of the binary format written by `to_bytes`.
+const FORMAT_VERSION: u8 = 4;
+
+/// Previous format version, without the hashing configuration in the header.
+/// Still accepted by `from_bytes`, with the default configuration.
+const UNCONFIGURED_FORMAT_VERSION: u8 = 3;
+
+/// Format version before 3, identical but without the trailing checksum.
+/// Still accepted by `from_bytes`.
+const UNCHECKED_FORMAT_VERSION: u8 = 2;
+
+/// Size of the trailing CRC32 checksum.
+const CHECKSUM_LEN: usize = 4;
+
+/// Size of the header fields shared by every format version: magic, version,
+/// then four 8-byte parameter fields and two 8-byte tracker counts.
+const BASE_HEADER_LEN: usize = MAGIC.len() + 1 + 6 * 8;
+
+/// Size of the binary header: the shared fields, one-byte hash backend, hash
+/// scheme and overfill behavior tags, then the four ahash keys and four
+/// seahash seeds of a keyed backend (zero for the others).
+const HEADER_LEN: usize = BASE_HEADER_LEN + 3 + 8 * 8;
+
+/// Offset of the insert count within the binary header.
+const ITEMS_FIELD_OFFSET: usize = MAGIC.len() + 1 + 4 * 8;
+
+/// Number of items queried side by side by `contains_batch_simd`.
+#[cfg(feature = "simd")]
+const BATCH_LANES: usize = 8;
+
+/// Policy for inserts once a filter holds its expected number of items.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverfillBehavior {
+    /// Keep accepting inserts; the false positive rate degrades gradually
+    #[default]
+    Allow,
+    /// Refuse inserts past capacity
+    Reject,
+}
+
+impl OverfillBehavior {
+    /// Get the tag identifying the behavior in the binary format.
+    fn tag(self) -> u8 {
+        match self {
+            OverfillBehavior::Allow => 0,
+            OverfillBehavior::Reject => 1,
+        }
+    }
+
+    /// Look up a behavior by its binary format tag.
+    fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(OverfillBehavior::Allow),
+            1 => Some(OverfillBehavior::Reject),
+            _ => None,
+        }
+    }
+}
+
+/// Fields decoded from the binary header.
+struct Header {
+    /// Filter parameters
+    params: BloomParameters,
+    /// Stored insert count
+    items_inserted: usize,
+    /// Stored query count
+    queries_performed: usize,
+    /// Hash functions the bits were placed with
+    backend: HashBackend,
+    /// Scheme the bits were placed with
+    scheme: DoubleHashScheme,
+    /// Policy for inserts past capacity
+    overfill_behavior: OverfillBehavior,
+    /// Length of the header, which depends on the format version
+    len: usize,
+}
+
+/// Describes how a filter hashes items and which build produced it.
+///
+/// Returned by [`PrecisionBloom::provenance`] for auditing mixed deployments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Provenance {
+    /// Name of the hash backend, from [`HashBackend::name`]
+    pub backend: &'static str,
+    /// Scheme deriving the k indices from the two base hashes, from
+    /// [`DoubleHashScheme::name`]
+    pub scheme: &'static str,
+    /// Binary format version written by `to_bytes`
+    pub format_version: u8,
+    /// Version of this crate
+    pub crate_version: &'static str,
+}
+
 /// A precision bloom filter optimized for accuracy.
 ///
 /// This bloom filter uses standard Kirsch-Mitzenmacher double hashing with two independent
 /// hash functions (ahash and seahash) for excellent hash distribution and minimal false positive rates.
+/// Both are keyed with fixed values, so an item maps to the same bits in every run.
 #[derive(Debug, Clone)]
 pub struct PrecisionBloom {
-    /// Bit array storing the filter state
-    bits: BitArray,
+    /// Bit array storing the filter state, shared with snapshots until written
+    bits: Arc<BitArray>,
     /// Hash strategy for generating indices
     hash_strategy: HashStrategy,
     /// Parameters of this filter
     params: BloomParameters,
     /// Accuracy tracking
     tracker: AccuracyTracker,
+    /// Policy for inserts past capacity
+    overfill_behavior: OverfillBehavior,
+    /// Whether queries increment the tracker's query count
+    count_queries: bool,
 }
 
 impl PrecisionBloom {
     /// Create a new bloom filter with specified parameters.
     ///
+    /// A `num_hashes` larger than `num_bits` is clamped with
+    /// [`BloomParameters::clamp_hashes`]. The filter's
+    /// [`num_hashes`](Self::num_hashes) is then below `params.num_hashes`,
+    /// which is how callers can tell that the clamp happened.
+    ///
     /// # Arguments
     /// * `params` - The bloom filter parameters
     pub fn new(params: BloomParameters) -> Self {
         params.validate().expect("Invalid parameters");
+        Self::with_bits(params, BitArray::new(params.num_bits))
+    }
+
+    /// Assemble a filter around an existing bit array.
+    ///
+    /// The caller must have validated `params` and checked that `bits` holds
+    /// `params.num_bits` bits. `num_hashes` is clamped as in [`new`](Self::new).
+    fn with_bits(mut params: BloomParameters, bits: BitArray) -> Self {
+        params.clamp_hashes();
 
-        let bits = BitArray::new(params.num_bits);
         let hash_strategy = HashStrategy::new(params.num_hashes, params.num_bits);
         let tracker = AccuracyTracker::new(params);
 
         Self {
-            bits,
+            bits: Arc::new(bits),
             hash_strategy,
             params,
             tracker,
+            overfill_behavior: OverfillBehavior::default(),
+            count_queries: false,
         }
     }
 
+    /// Set the policy for inserts past the filter's capacity.
+    ///
+    /// # Example
+    /// ```
+    /// use bloom_filter_rs::{OverfillBehavior, PrecisionBloom};
+    ///
+    /// let filter = PrecisionBloom::with_capacity(1000, 0.01)
+    ///     .with_overfill_behavior(OverfillBehavior::Reject);
+    /// assert_eq!(filter.overfill_behavior(), OverfillBehavior::Reject);
+    /// ```
+    pub fn with_overfill_behavior(mut self, behavior: OverfillBehavior) -> Self {
+        self.overfill_behavior = behavior;
+        self
+    }
+
+    /// Set the hash functions used to place items.
+    ///
+    /// The binary format records the backend, so restored filters do not
+    /// need this.
+    ///
+    /// # Panics
+    /// Panics if the filter already has bits set and `backend` differs from
+    /// its current one, since those items would no longer be found.
+    pub fn with_hash_backend(mut self, backend: HashBackend) -> Self {
+        assert!(
+            backend == self.hash_strategy.backend() || self.bits.count_ones() == 0,
+            "cannot change the hash backend of a non-empty filter"
+        );
+        self.hash_strategy =
+            HashStrategy::with_backend(self.params.num_hashes, self.params.num_bits, backend)
+                .with_scheme(self.hash_strategy.scheme());
+        self
+    }
+
+    /// Set the scheme used to derive bit positions from the base hashes.
+    ///
+    /// Like the hash backend, the scheme is recorded in the binary format.
+    ///
+    /// # Panics
+    /// Panics if the filter already has bits set and `scheme` differs from
+    /// its current one, since those items would no longer be found.
+    pub fn with_hash_scheme(mut self, scheme: DoubleHashScheme) -> Self {
+        assert!(
+            scheme == self.hash_strategy.scheme() || self.bits.count_ones() == 0,
+            "cannot change the hash scheme of a non-empty filter"
+        );
+        self.hash_strategy = self.hash_strategy.with_scheme(scheme);
+        self
+    }
+
+    /// Create a new bloom filter whose hashers use explicit keys.
+    ///
+    /// The default backend uses keys built into the crate, so anyone can
+    /// predict where an item lands; custom keys prevent that. The keys are
+    /// stored by [`to_bytes`](Self::to_bytes). See [`HashBackend::Keyed`] for
+    /// the remaining caveats.
+    ///
+    /// # Example
+    /// ```
+    /// use bloom_filter_rs::{BloomParameters, PrecisionBloom};
+    ///
+    /// let params = BloomParameters::from_item_count(100, 0.01);
+    /// let mut a = PrecisionBloom::with_fixed_keys(params, [1, 2, 3, 4], [5, 6, 7, 8]);
+    /// let mut b = PrecisionBloom::with_fixed_keys(params, [1, 2, 3, 4], [5, 6, 7, 8]);
+    ///
+    /// a.insert(&"hello");
+    /// b.insert(&"hello");
+    /// assert_eq!(a, b);
+    /// ```
+    pub fn with_fixed_keys(
+        params: BloomParameters,
+        ahash_keys: [u64; 4],
+        seahash_seeds: [u64; 4],
+    ) -> Self {
+        Self::new(params).with_hash_backend(HashBackend::Keyed {
+            ahash_keys,
+            seahash_seeds,
+        })
+    }
+
+    /// Enable or disable counting of membership queries.
+    ///
+    /// When enabled, `contains` and its variants increment the tracker's
+    /// query count, reported by [`queries_performed`](Self::queries_performed).
+    /// Counting is off by default to keep queries free of shared writes.
+    ///
+    /// # Example
+    /// ```
+    /// use bloom_filter_rs::PrecisionBloom;
+    ///
+    /// let filter = PrecisionBloom::with_capacity(1000, 0.01).with_query_counting(true);
+    /// filter.contains(&"hello");
+    /// assert_eq!(filter.queries_performed(), 1);
+    /// ```
+    pub fn with_query_counting(mut self, enabled: bool) -> Self {
+        self.count_queries = enabled;
+        self
+    }
+
     /// Create a new bloom filter for a given number of items and false positive rate.
     ///
     /// This is the recommended constructor for most use cases.
@@ -63,6 +305,311 @@ impl PrecisionBloom {
         Self::new(params)
     }
 
+    /// Build a filter sized for `items` and insert all of them.
+    ///
+    /// An empty slice yields a filter sized for one item.
+    ///
+    /// # Example
+    /// ```
+    /// use bloom_filter_rs::PrecisionBloom;
+    ///
+    /// let words = vec!["apple", "banana", "cherry"];
+    /// let filter = PrecisionBloom::from_set(&words, 0.01);
+    ///
+    /// assert!(filter.contains(&"banana"));
+    /// assert_eq!(filter.len(), 3);
+    /// ```
+    pub fn from_set<T: Hash>(items: &[T], false_positive_rate: f64) -> Self {
+        let mut filter = Self::with_capacity(items.len().max(1), false_positive_rate);
+        for item in items {
+            filter.insert(item);
+        }
+        filter
+    }
+
+    /// Build a filter from items known to be unique, such as a sorted,
+    /// deduplicated key file.
+    ///
+    /// The filter is sized for exactly `items.len()` items. Bits are set
+    /// directly, skipping the per-insert capacity check and tracker update,
+    /// and the item count is recorded once at the end. Order does not matter;
+    /// if the items are not actually unique, `len()` overcounts.
+    ///
+    /// # Example
+    /// ```
+    /// use bloom_filter_rs::PrecisionBloom;
+    ///
+    /// let keys = vec!["apple", "banana", "cherry"];
+    /// let filter = PrecisionBloom::from_unique_sorted(keys.iter(), 0.01);
+    ///
+    /// assert_eq!(filter.len(), 3);
+    /// assert!(filter.contains(&"banana"));
+    /// ```
+    pub fn from_unique_sorted<T: Hash>(
+        items: impl ExactSizeIterator<Item = T>,
+        false_positive_rate: f64,
+    ) -> Self {
+        let count = items.len();
+        let mut filter = Self::with_capacity(count.max(1), false_positive_rate);
+
+        let bits = Arc::make_mut(&mut filter.bits);
+        for item in items {
+            for index in filter.hash_strategy.hash_indices(&item) {
+                bits.set(index);
+            }
+        }
+
+        filter.tracker.set_counts(count, 0);
+        filter
+    }
+
+    /// Check that inserting `items` forwards and in reverse yields equal filters.
+    ///
+    /// Bloom filters are insert-order independent by construction; this is a
+    /// sanity check for a given data set, comparing bits, parameters and hash
+    /// configuration.
+    pub fn is_order_independent<T: Hash + Clone>(items: &[T], false_positive_rate: f64) -> bool {
+        let forward = Self::from_set(items, false_positive_rate);
+
+        let reversed: Vec<T> = items.iter().rev().cloned().collect();
+        let backward = Self::from_set(&reversed, false_positive_rate);
+
+        forward == backward
+    }
+
+    /// Build a filter over `present` that reports no false positives for any
+    /// item in `absent`, starting the search at `start_fpr`.
+    ///
+    /// Each round builds a filter over `present`, checks every item in `absent`,
+    /// and halves the target false positive rate if any of them is reported
+    /// present. The search is bounded; if no round is clean, the filter from
+    /// the last (tightest) round is returned.
+    ///
+    /// # Arguments
+    /// * `present` - Items the filter must contain
+    /// * `absent` - Items the filter should reject
+    /// * `start_fpr` - First target false positive rate to try
+    pub fn fit_for_sets<T: Hash>(present: &[T], absent: &[T], start_fpr: f64) -> Self {
+        let mut fpr = start_fpr;
+        let mut round = 0;
+
+        loop {
+            let mut filter = Self::with_capacity(present.len().max(1), fpr);
+            for item in present {
+                filter.insert(item);
+            }
+
+            round += 1;
+            let clean = absent.iter().all(|item| !filter.contains(item));
+            if clean || round == MAX_FIT_ROUNDS {
+                return filter;
+            }
+
+            fpr /= 2.0;
+        }
+    }
+
+    /// Measure the false positive rate each [`DoubleHashScheme`] achieves on
+    /// the same data.
+    ///
+    /// For every scheme, a filter sized for `items` at `false_positive_rate`
+    /// is filled with `items`, and the fraction of `absent` it reports present
+    /// is recorded. All filters share the default hash backend, so only the
+    /// index derivation differs. Returns one `(scheme, measured_fpr)` entry
+    /// per scheme, in [`DoubleHashScheme::ALL`] order; the rate is 0 if
+    /// `absent` is empty.
+    ///
+    /// # Example
+    /// ```
+    /// use bloom_filter_rs::{DoubleHashScheme, PrecisionBloom};
+    ///
+    /// let items: Vec<u32> = (0..1000).collect();
+    /// let absent: Vec<u32> = (1000..11_000).collect();
+    ///
+    /// let results = PrecisionBloom::compare_schemes(&items, &absent, 0.01);
+    /// assert_eq!(results.len(), DoubleHashScheme::ALL.len());
+    /// ```
+    pub fn compare_schemes<T: Hash + Clone>(
+        items: &[T],
+        absent: &[T],
+        false_positive_rate: f64,
+    ) -> Vec<(DoubleHashScheme, f64)> {
+        DoubleHashScheme::ALL
+            .iter()
+            .map(|&scheme| {
+                let mut filter = Self::with_capacity(items.len().max(1), false_positive_rate)
+                    .with_hash_scheme(scheme);
+                for item in items {
+                    filter.insert(item);
+                }
+
+                let positives = absent.iter().filter(|item| filter.contains(item)).count();
+                (scheme, positives as f64 / absent.len().max(1) as f64)
+            })
+            .collect()
+    }
+
+    /// Reconstruct a filter from its parameters and an existing bit array.
+    ///
+    /// The tracker starts at zero inserts; call
+    /// [`estimate_and_set_item_count`](Self::estimate_and_set_item_count) to
+    /// seed it from the bits.
+    ///
+    /// # Errors
+    /// Returns `BloomError::InvalidFormat` if the parameters are invalid or the
+    /// bit array's capacity does not match `params.num_bits`.
+    ///
+    /// # Example
+    /// ```
+    /// use bloom_filter_rs::{BitArray, BloomParameters, PrecisionBloom};
+    ///
+    /// let params = BloomParameters::from_item_count(100, 0.01);
+    /// let filter = PrecisionBloom::from_parts(params, BitArray::new(params.num_bits)).unwrap();
+    /// assert!(filter.is_empty());
+    /// ```
+    pub fn from_parts(params: BloomParameters, bits: BitArray) -> Result<Self, BloomError> {
+        params.validate().map_err(BloomError::InvalidFormat)?;
+        if bits.capacity() != params.num_bits {
+            return Err(BloomError::InvalidFormat(format!(
+                "bit array holds {} bits, parameters expect {}",
+                bits.capacity(),
+                params.num_bits
+            )));
+        }
+
+        Ok(Self::with_bits(params, bits))
+    }
+
+    /// Build a filter from precomputed `(h1, h2)` base hash pairs.
+    ///
+    /// Each pair is expanded into bit positions with the same double-hashing
+    /// scheme `insert` uses, so a filter's contents can be ported from another
+    /// library without the original items. Items only match if the pairs
+    /// were produced by this filter's hash backend.
+    ///
+    /// # Panics
+    /// Panics if the parameters are invalid.
+    pub fn from_prehashed(
+        params: BloomParameters,
+        pairs: impl IntoIterator<Item = (u64, u64)>,
+    ) -> Self {
+        let mut filter = Self::new(params);
+        for (h1, h2) in pairs {
+            filter.insert_base_hashes(h1, h2);
+        }
+        filter
+    }
+
+    /// Create an empty filter with new parameters and this filter's hashing
+    /// configuration (backend and scheme) and overfill behavior.
+    fn empty_with_params(&self, params: BloomParameters) -> Self {
+        let mut filter = Self::new(params);
+        filter.hash_strategy = HashStrategy::with_backend(
+            params.num_hashes,
+            params.num_bits,
+            self.hash_strategy.backend(),
+        )
+        .with_scheme(self.hash_strategy.scheme());
+        filter.overfill_behavior = self.overfill_behavior;
+        filter.count_queries = self.count_queries;
+        filter
+    }
+
+    /// Create an empty filter with exactly the same geometry as `other`.
+    ///
+    /// The parameters, hash backend (including any fixed keys) and overfill
+    /// behavior are copied as-is, however `other` was sized, so the result is
+    /// always [union-compatible](Self::is_union_compatible) with it.
+    pub fn empty_like(other: &PrecisionBloom) -> Self {
+        other.empty_with_params(other.params)
+    }
+
+    /// Create `n` empty filters with this filter's parameters and hashing
+    /// configuration, to be filled with [`sharded_insert`](Self::sharded_insert).
+    ///
+    /// A bloom filter cannot enumerate its items, so existing contents cannot
+    /// be split; sharding has to happen as items are inserted.
+    ///
+    /// # Panics
+    /// Panics if `n` is 0
+    pub fn split_by_hash(&self, n: usize) -> Vec<PrecisionBloom> {
+        assert!(n > 0, "n must be greater than 0");
+        (0..n)
+            .map(|_| self.empty_with_params(self.params))
+            .collect()
+    }
+
+    /// Insert an item into the shard chosen by [`shard_of`].
+    ///
+    /// The same item always routes to the same shard, so a query only needs
+    /// to check `shards[shard_of(item, shards.len())]`. Returns the index of
+    /// the shard the item went to.
+    ///
+    /// # Example
+    /// ```
+    /// use bloom_filter_rs::{shard_of, PrecisionBloom};
+    ///
+    /// let template = PrecisionBloom::with_capacity(1000, 0.01);
+    /// let mut shards = template.split_by_hash(4);
+    ///
+    /// let shard = PrecisionBloom::sharded_insert(&mut shards, &"hello");
+    /// assert_eq!(shard, shard_of(&"hello", 4));
+    /// assert!(shards[shard].contains(&"hello"));
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `shards` is empty, or if the chosen shard is at capacity and
+    /// its overfill behavior is [`OverfillBehavior::Reject`].
+    pub fn sharded_insert<T: Hash>(shards: &mut [PrecisionBloom], item: &T) -> usize {
+        let shard = shard_of(item, shards.len());
+        shards[shard].insert(item);
+        shard
+    }
+
+    /// Build a right-sized replacement filter from the items it should hold.
+    ///
+    /// A bloom filter cannot enumerate its items, so shrinking requires
+    /// re-supplying them. The result is sized for `items.len()` at
+    /// `target_fpr` and uses the same hash backend as this filter.
+    ///
+    /// # Example
+    /// ```
+    /// use bloom_filter_rs::PrecisionBloom;
+    ///
+    /// let filter = PrecisionBloom::with_capacity(100_000, 0.01);
+    /// let items: Vec<u32> = (0..100).collect();
+    ///
+    /// let compact = filter.compact_from_items(&items, 0.01);
+    /// assert!(compact.num_bits() < filter.num_bits());
+    /// ```
+    pub fn compact_from_items<T: Hash>(&self, items: &[T], target_fpr: f64) -> Self {
+        let params = BloomParameters::from_item_count(items.len().max(1), target_fpr);
+
+        let mut filter = self.empty_with_params(params);
+        for item in items {
+            filter.insert(item);
+        }
+        filter
+    }
+
+    /// Rebuild the filter at a new target false positive rate from the items
+    /// it should hold, keeping the same hash backend.
+    ///
+    /// Unlike [`compact_from_items`](Self::compact_from_items), the rebuilt
+    /// filter keeps this filter's capacity (or `items.len()`, if larger), so
+    /// it has the same headroom at the tighter rate. There is no per-filter
+    /// seed; the backend alone fixes how items are hashed.
+    pub fn with_same_hashers_at_fpr<T: Hash>(&self, items: &[T], new_fpr: f64) -> Self {
+        let expected_items = self.capacity().max(items.len());
+        let params = BloomParameters::from_item_count(expected_items, new_fpr);
+
+        let mut filter = self.empty_with_params(params);
+        for item in items {
+            filter.insert(item);
+        }
+        filter
+    }
+
     /// Insert an item into the bloom filter.
     ///
     /// # Arguments
@@ -80,22 +627,209 @@ impl PrecisionBloom {
     /// filter.insert(&"hello");
     /// filter.insert(&42);
     /// ```
+    ///
+    /// # Panics
+    /// Panics if the filter is at capacity and its overfill behavior is
+    /// [`OverfillBehavior::Reject`]; use [`try_insert`](Self::try_insert) to
+    /// handle that case.
     pub fn insert<T: Hash>(&mut self, item: &T) -> bool {
-        self.tracker.record_insert();
+        self.assert_capacity();
+
+        // A single hash function needs neither the secondary hash nor a Vec
+        if self.params.num_hashes == 1 {
+            let index = self.hash_strategy.single_index(item);
+            return self.insert_indices(&[index]);
+        }
 
         let indices = self.hash_strategy.hash_indices(item);
-        let mut was_absent = false;
+        self.insert_indices(&indices)
+    }
+
+    /// Insert an item, respecting the filter's overfill behavior.
+    ///
+    /// # Errors
+    /// Returns `BloomError::CapacityExceeded` if the filter is at capacity and
+    /// its overfill behavior is [`OverfillBehavior::Reject`].
+    ///
+    /// # Example
+    /// ```
+    /// use bloom_filter_rs::{OverfillBehavior, PrecisionBloom};
+    ///
+    /// let mut filter = PrecisionBloom::with_capacity(1, 0.01)
+    ///     .with_overfill_behavior(OverfillBehavior::Reject);
+    ///
+    /// assert!(filter.try_insert(&"first").is_ok());
+    /// assert!(filter.try_insert(&"second").is_err());
+    /// ```
+    pub fn try_insert<T: Hash>(&mut self, item: &T) -> Result<bool, BloomError> {
+        self.check_capacity()?;
+
+        let indices = self.hash_strategy.hash_indices(item);
+        Ok(self.insert_indices(&indices))
+    }
+
+    /// Insert an item, setting each of its bits only with probability `p`.
+    ///
+    /// Repeated inserts make membership frequency-sensitive: frequent items
+    /// end up with all their bits set, rare items only some of them. This
+    /// breaks the no-false-negative guarantee, since an inserted item may still
+    /// be reported absent. Each call counts as one insert.
+    ///
+    /// # Panics
+    /// Panics if `p` is not between 0 and 1, or if the filter is at capacity
+    /// and its overfill behavior is [`OverfillBehavior::Reject`].
+    #[cfg(feature = "rand")]
+    pub fn insert_with_probability<T: Hash>(&mut self, item: &T, p: f64, rng: &mut impl Rng) {
+        assert!((0.0..=1.0).contains(&p), "p must be between 0 and 1");
+        self.assert_capacity();
+
+        let indices: Vec<usize> = self
+            .hash_strategy
+            .hash_indices(item)
+            .into_iter()
+            .filter(|_| rng.gen_bool(p))
+            .collect();
+        self.insert_indices(&indices);
+    }
+
+    /// Record an insertion and set the given bit indices.
+    ///
+    /// Returns `true` if any of the bits was previously unset.
+    fn insert_indices(&mut self, indices: &[usize]) -> bool {
+        self.tracker.record_insert();
 
-        for &index in &indices {
+        let mut was_absent = false;
+        for &index in indices {
             if !self.bits.get(index) {
                 was_absent = true;
-                self.bits.set(index);
+                Arc::make_mut(&mut self.bits).set(index);
             }
         }
 
         was_absent
     }
 
+    /// Check whether another insert is allowed by the overfill behavior.
+    fn check_capacity(&self) -> Result<(), BloomError> {
+        match self.overfill_behavior {
+            OverfillBehavior::Allow => Ok(()),
+            OverfillBehavior::Reject if self.len() >= self.capacity() => {
+                Err(BloomError::CapacityExceeded {
+                    capacity: self.capacity(),
+                })
+            }
+            OverfillBehavior::Reject => Ok(()),
+        }
+    }
+
+    /// Panic if another insert is not allowed by the overfill behavior.
+    fn assert_capacity(&self) {
+        if let Err(err) = self.check_capacity() {
+            panic!("{}", err);
+        }
+    }
+
+    /// Insert every integer in a range.
+    ///
+    /// Each value is hashed as a `u64`, exactly as `insert(&value)` would, and
+    /// counts as one insert. Filters with a single hash function take the
+    /// same allocation-free fast path as `insert`.
+    ///
+    /// # Panics
+    /// Panics if the filter reaches capacity and its overfill behavior is
+    /// [`OverfillBehavior::Reject`].
+    pub fn insert_range(&mut self, range: Range<u64>) {
+        for value in range {
+            self.insert(&value);
+        }
+    }
+
+    /// Insert an item only if the filter does not already report it present.
+    ///
+    /// The outcome feeds the tracker's sliding novelty window, exposed via
+    /// [`recent_novelty_rate`](Self::recent_novelty_rate). Items already
+    /// reported present are not counted as inserts.
+    ///
+    /// # Returns
+    /// Returns `true` if the item was new and has been inserted, `false` if it
+    /// was probably present already (could be a false positive).
+    pub fn insert_if_absent<T: Hash>(&mut self, item: &T) -> bool {
+        let was_new = !self.contains(item);
+        if was_new {
+            self.insert(item);
+        }
+
+        self.tracker.record_novelty(was_new);
+        was_new
+    }
+
+    /// Insert items until the theoretical false positive rate would exceed `max_fpr`.
+    ///
+    /// The item count at which the rate crosses `max_fpr` is computed up
+    /// front with [`BloomParameters::max_items_for_fpr`], so no insert ever
+    /// pushes the filter past the limit.
+    ///
+    /// # Returns
+    /// The number of items inserted, and whether insertion stopped before the
+    /// iterator was exhausted.
+    ///
+    /// # Example
+    /// ```
+    /// use bloom_filter_rs::PrecisionBloom;
+    ///
+    /// let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+    /// let (inserted, stopped_early) = filter.insert_until_fpr(0..100_000, 0.02);
+    ///
+    /// assert!(stopped_early);
+    /// assert!(inserted < 100_000);
+    /// assert!(filter.actual_false_positive_rate() <= 0.02);
+    /// ```
+    pub fn insert_until_fpr<T: Hash>(
+        &mut self,
+        iter: impl IntoIterator<Item = T>,
+        max_fpr: f64,
+    ) -> (usize, bool) {
+        let limit = self.params.max_items_for_fpr(max_fpr);
+        let mut iter = iter.into_iter().peekable();
+        let mut inserted = 0;
+
+        while self.len() < limit {
+            let Some(item) = iter.next() else {
+                return (inserted, false);
+            };
+            self.insert(&item);
+            inserted += 1;
+        }
+
+        (inserted, iter.peek().is_some())
+    }
+
+    /// Adapt an iterator to yield only items the filter has not seen before.
+    ///
+    /// Each item goes through [`insert_if_absent`](Self::insert_if_absent) and
+    /// is yielded only if it was new. A false positive makes a genuinely new
+    /// item look seen, so roughly a false-positive-rate fraction of new items
+    /// may be dropped; no duplicate is ever yielded twice.
+    ///
+    /// # Example
+    /// ```
+    /// use bloom_filter_rs::PrecisionBloom;
+    ///
+    /// let mut filter = PrecisionBloom::with_capacity(100, 0.01);
+    /// let unique: Vec<i32> = filter.dedup_iter(vec![1, 1, 2, 2, 3]).collect();
+    ///
+    /// assert_eq!(unique, vec![1, 2, 3]);
+    /// ```
+    pub fn dedup_iter<'a, I, T>(&'a mut self, iter: I) -> impl Iterator<Item = T> + 'a
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: 'a,
+        T: Hash,
+    {
+        iter.into_iter()
+            .filter(move |item| self.insert_if_absent(item))
+    }
+
     /// Check if an item might be in the bloom filter.
     ///
     /// # Arguments
@@ -116,29 +850,454 @@ impl PrecisionBloom {
     /// assert!(!filter.contains(&"world")); // Never inserted
     /// ```
     pub fn contains<T: Hash>(&self, item: &T) -> bool {
+        self.note_query();
+        if self.params.num_hashes == 1 {
+            return self.bits.get(self.hash_strategy.single_index(item));
+        }
+
         let indices = self.hash_strategy.hash_indices(item);
 
         // Item is present only if ALL hash positions are set
         indices.iter().all(|&index| self.bits.get(index))
     }
 
-    /// Check if an item might be in the bloom filter (alias for contains).
+    /// Check if an item might be in the filter, querying by a borrowed form.
     ///
-    /// This method is provided for clarity in some contexts.
-    #[inline]
-    pub fn may_contain<T: Hash>(&self, item: &T) -> bool {
-        self.contains(item)
+    /// Follows the `HashMap::get` pattern: if items were inserted as `K` and
+    /// `K: Borrow<Q>`, the `Borrow` contract guarantees `K` and `Q` hash the
+    /// same, so a filter of `String`s can be queried with a `&str` without
+    /// allocating. Unlike [`contains`](Self::contains), `Q` may be unsized.
+    ///
+    /// # Example
+    /// ```
+    /// use bloom_filter_rs::PrecisionBloom;
+    ///
+    /// let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+    /// filter.insert(&String::from("hello"));
+    ///
+    /// assert!(filter.contains_borrowed::<str>("hello"));
+    /// ```
+    pub fn contains_borrowed<Q: Hash + ?Sized>(&self, key: &Q) -> bool {
+        self.contains(&key)
     }
 
-    /// Clear all items from the filter.
+    /// Scan a buffer for byte windows the filter reports as present.
     ///
-    /// Resets the filter to its initial empty state.
-    pub fn clear(&mut self) {
-        self.bits.clear();
-        self.tracker.reset();
+    /// Checks every `window`-byte slice of `data` and returns the start offsets
+    /// of those that match, in increasing order. Windows are hashed as `&[u8]`,
+    /// so they match slices or `Vec<u8>`s inserted with [`insert`](Self::insert).
+    /// Like any membership query, offsets may include false positives. Returns
+    /// no offsets if `data` is shorter than `window`.
+    ///
+    /// # Example
+    /// ```
+    /// use bloom_filter_rs::PrecisionBloom;
+    ///
+    /// let mut filter = PrecisionBloom::with_capacity(100, 0.001);
+    /// filter.insert(&b"needle".as_slice());
+    ///
+    /// assert_eq!(filter.contains_windows(b"haystack needle hay", 6), vec![9]);
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if window is 0
+    pub fn contains_windows(&self, data: &[u8], window: usize) -> Vec<usize> {
+        assert!(window > 0, "window must be greater than 0");
+
+        data.windows(window)
+            .enumerate()
+            .filter(|(_, slice)| self.contains(slice))
+            .map(|(offset, _)| offset)
+            .collect()
     }
 
-    /// Get the number of items inserted into the filter.
+    /// Check a batch of items, returning one answer per item.
+    ///
+    /// With the `simd` feature, items are processed in groups of eight: the
+    /// base hashes for the whole group are computed first, then each hash
+    /// round loads and tests one word per item in fixed-size arrays the
+    /// compiler can vectorize, stopping early once every item in the group is
+    /// ruled out. This stays on stable Rust without `std::simd` or unsafe
+    /// intrinsics. Without the feature, each item is checked with
+    /// [`contains`](Self::contains). Both paths give identical answers.
+    pub fn contains_batch_simd<T: Hash>(&self, items: &[T]) -> Vec<bool> {
+        #[cfg(feature = "simd")]
+        {
+            self.contains_batch_lanes(items)
+        }
+        #[cfg(not(feature = "simd"))]
+        {
+            items.iter().map(|item| self.contains(item)).collect()
+        }
+    }
+
+    /// Lane-parallel implementation of `contains_batch_simd`.
+    #[cfg(feature = "simd")]
+    fn contains_batch_lanes<T: Hash>(&self, items: &[T]) -> Vec<bool> {
+        let words = self.bits.as_words();
+        let mut results = Vec::with_capacity(items.len());
+
+        let mut groups = items.chunks_exact(BATCH_LANES);
+        for group in &mut groups {
+            let mut h1 = [0u64; BATCH_LANES];
+            let mut h2 = [0u64; BATCH_LANES];
+            for (lane, item) in group.iter().enumerate() {
+                (h1[lane], h2[lane]) = self.hash_strategy.base_hashes(item);
+                self.note_query();
+            }
+
+            // Each lane holds 1 while its item is still possibly present
+            let mut present = [1u64; BATCH_LANES];
+            for i in 0..self.params.num_hashes {
+                let mut bits = [0u64; BATCH_LANES];
+                for lane in 0..BATCH_LANES {
+                    let index = self.hash_strategy.compute_index(h1[lane], h2[lane], i);
+                    let (word_index, bit_index) = BitArray::locate(index);
+                    bits[lane] = words[word_index] >> bit_index;
+                }
+                for lane in 0..BATCH_LANES {
+                    present[lane] &= bits[lane];
+                }
+                if present.iter().all(|&lane| lane & 1 == 0) {
+                    break;
+                }
+            }
+
+            results.extend(present.iter().map(|&lane| lane & 1 == 1));
+        }
+
+        for item in groups.remainder() {
+            results.push(self.contains(item));
+        }
+        results
+    }
+
+    /// Count a query if query counting is enabled.
+    #[inline]
+    fn note_query(&self) {
+        if self.count_queries {
+            self.tracker.record_query();
+        }
+    }
+
+    /// Record a query in the tracker, regardless of whether counting is enabled.
+    ///
+    /// Useful for counting lookups made through a path the filter does not
+    /// see, such as a cache in front of it.
+    pub fn record_query(&self) {
+        self.tracker.record_query();
+    }
+
+    /// Get the number of queries recorded by the tracker.
+    pub fn queries_performed(&self) -> usize {
+        self.tracker.queries_performed()
+    }
+
+    /// Check that every item in a batch might be in the filter.
+    ///
+    /// Stops at the first item reported absent.
+    ///
+    /// # Errors
+    /// Returns `Err(i)` where `i` is the index of the first absent item.
+    ///
+    /// # Example
+    /// ```
+    /// use bloom_filter_rs::PrecisionBloom;
+    ///
+    /// let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+    /// for item in ["a", "b", "c"] {
+    ///     filter.insert(&item);
+    /// }
+    ///
+    /// assert_eq!(filter.probably_contains_all(&["a", "c"]), Ok(()));
+    /// assert_eq!(filter.probably_contains_all(&["a", "zzz", "c"]), Err(1));
+    /// ```
+    pub fn probably_contains_all<T: Hash>(&self, items: &[T]) -> Result<(), usize> {
+        match items.iter().position(|item| !self.contains(item)) {
+            Some(index) => Err(index),
+            None => Ok(()),
+        }
+    }
+
+    /// Count how many items in a batch might be in the filter.
+    ///
+    /// Estimates the overlap between a candidate set and the filter; the
+    /// count can exceed the true overlap by the false positive rate.
+    pub fn count_probable_members<T: Hash>(&self, items: &[T]) -> usize {
+        items.iter().filter(|item| self.contains(item)).count()
+    }
+
+    /// Insert a key using its canonical [`BloomKey`] encoding.
+    ///
+    /// Keys inserted this way must be queried with [`contains_key`](Self::contains_key);
+    /// the encoding differs from the `Hash` path used by `insert`.
+    ///
+    /// # Returns
+    /// Returns `true` if the key was definitely not in the filter before,
+    /// `false` if it might have been (could be a false positive).
+    ///
+    /// # Panics
+    /// Panics if the filter is at capacity and its overfill behavior is
+    /// [`OverfillBehavior::Reject`].
+    ///
+    /// # Example
+    /// ```
+    /// use bloom_filter_rs::PrecisionBloom;
+    ///
+    /// let mut filter = PrecisionBloom::with_capacity(100, 0.01);
+    /// filter.insert_key(&1u32);
+    ///
+    /// // Integers are widened, so the same value matches regardless of type
+    /// assert!(filter.contains_key(&1u64));
+    /// ```
+    pub fn insert_key<K: BloomKey + ?Sized>(&mut self, key: &K) -> bool {
+        self.assert_capacity();
+
+        let indices = self.hash_strategy.key_indices(key);
+        self.insert_indices(&indices)
+    }
+
+    /// Start hashing a byte key that arrives in chunks.
+    ///
+    /// The returned [`BloomKeyHasher`] produces the same bits as
+    /// [`insert_key`](Self::insert_key) on the concatenated bytes, so streamed
+    /// keys can be queried either way.
+    pub fn key_hasher(&self) -> BloomKeyHasher {
+        let (h1, h2) = self.hash_strategy.key_hashers();
+        BloomKeyHasher::new(h1, h2)
+    }
+
+    /// Compute an item's two base hashes with this filter's backend.
+    pub(crate) fn item_base_hashes<T: Hash>(&self, item: &T) -> (u64, u64) {
+        self.hash_strategy.base_hashes(item)
+    }
+
+    /// Insert a key from its two base hashes.
+    pub(crate) fn insert_base_hashes(&mut self, h1: u64, h2: u64) -> bool {
+        self.assert_capacity();
+
+        let indices = self.hash_strategy.indices_from_hashes(h1, h2);
+        self.insert_indices(&indices)
+    }
+
+    /// Check a key from its two base hashes.
+    pub(crate) fn contains_base_hashes(&self, h1: u64, h2: u64) -> bool {
+        self.note_query();
+        let indices = self.hash_strategy.indices_from_hashes(h1, h2);
+        indices.iter().all(|&index| self.bits.get(index))
+    }
+
+    /// Insert a 128-bit integer using its 16 little-endian bytes.
+    ///
+    /// The encoding is the same on every platform and the default backend
+    /// hashes with fixed keys, so a filter of 128-bit IDs saved with
+    /// [`to_bytes`](Self::to_bytes) can be queried by any process. Equivalent
+    /// to `insert_key(&value)`.
+    pub fn insert_u128(&mut self, value: u128) -> bool {
+        self.insert_key(&value)
+    }
+
+    /// Check if a 128-bit integer inserted with [`insert_u128`](Self::insert_u128) might be in the filter.
+    pub fn contains_u128(&self, value: u128) -> bool {
+        self.contains_key(&value)
+    }
+
+    /// Check if a key inserted with [`insert_key`](Self::insert_key) might be in the filter.
+    pub fn contains_key<K: BloomKey + ?Sized>(&self, key: &K) -> bool {
+        self.note_query();
+        let indices = self.hash_strategy.key_indices(key);
+        indices.iter().all(|&index| self.bits.get(index))
+    }
+
+    /// Insert a serde-serializable item by hashing its canonical bincode bytes.
+    ///
+    /// This avoids relying on a derived `Hash`, whose behavior depends on field
+    /// framing. Items inserted this way must be queried with
+    /// [`contains_serde`](Self::contains_serde).
+    ///
+    /// # Errors
+    /// Returns `BloomError::Serialization` if the item cannot be serialized.
+    #[cfg(feature = "serde")]
+    pub fn insert_serde<T: serde::Serialize + ?Sized>(
+        &mut self,
+        item: &T,
+    ) -> Result<(), BloomError> {
+        let bytes = Self::serde_bytes(item)?;
+        self.insert_key(bytes.as_slice());
+        Ok(())
+    }
+
+    /// Check if an item inserted with [`insert_serde`](Self::insert_serde) might be in the filter.
+    ///
+    /// # Errors
+    /// Returns `BloomError::Serialization` if the item cannot be serialized.
+    #[cfg(feature = "serde")]
+    pub fn contains_serde<T: serde::Serialize + ?Sized>(
+        &self,
+        item: &T,
+    ) -> Result<bool, BloomError> {
+        let bytes = Self::serde_bytes(item)?;
+        Ok(self.contains_key(bytes.as_slice()))
+    }
+
+    /// Serialize an item into the canonical bytes used for hashing.
+    #[cfg(feature = "serde")]
+    fn serde_bytes<T: serde::Serialize + ?Sized>(item: &T) -> Result<Vec<u8>, BloomError> {
+        bincode::serialize(item).map_err(|err| BloomError::Serialization(err.to_string()))
+    }
+
+    /// Insert a string after applying Unicode NFC normalization.
+    ///
+    /// Composed and decomposed forms of the same text (such as `"café"` with
+    /// a precomposed `é` or with `e` plus a combining accent) normalize to the
+    /// same bytes and are hashed as the same key. Strings inserted this way
+    /// must be queried with [`contains_str_normalized`](Self::contains_str_normalized).
+    ///
+    /// # Panics
+    /// Panics if the filter is at capacity and its overfill behavior is
+    /// [`OverfillBehavior::Reject`].
+    #[cfg(feature = "unicode")]
+    pub fn insert_str_normalized(&mut self, s: &str) -> bool {
+        use unicode_normalization::UnicodeNormalization;
+
+        let normalized: String = s.nfc().collect();
+        self.insert_key(normalized.as_str())
+    }
+
+    /// Check if a string inserted with [`insert_str_normalized`](Self::insert_str_normalized)
+    /// might be in the filter.
+    #[cfg(feature = "unicode")]
+    pub fn contains_str_normalized(&self, s: &str) -> bool {
+        use unicode_normalization::UnicodeNormalization;
+
+        let normalized: String = s.nfc().collect();
+        self.contains_key(normalized.as_str())
+    }
+
+    /// Get the probability that an item reported present is really in the set,
+    /// given a prior probability that it is.
+    ///
+    /// By Bayes' rule, with no false negatives and false positive rate `f`
+    /// from [`actual_false_positive_rate`](Self::actual_false_positive_rate):
+    ///
+    /// P(present | positive) = prior / (prior + (1 - prior) * f)
+    ///
+    /// With a small prior, false positives dominate and the posterior stays
+    /// low even though the filter said yes.
+    ///
+    /// # Panics
+    /// Panics if prior is not between 0 and 1
+    pub fn posterior_present_probability(&self, prior: f64) -> f64 {
+        assert!(
+            (0.0..=1.0).contains(&prior),
+            "prior must be between 0 and 1"
+        );
+
+        let evidence = prior + (1.0 - prior) * self.actual_false_positive_rate();
+        if evidence == 0.0 {
+            return 0.0;
+        }
+        prior / evidence
+    }
+
+    /// Check membership and report the confidence in the answer.
+    ///
+    /// On a positive, the confidence is `1 - actual_false_positive_rate()`, the
+    /// probability the positive is real at the current fill level. On a negative
+    /// it is `1.0`, since bloom filters have no false negatives.
+    ///
+    /// # Example
+    /// ```
+    /// use bloom_filter_rs::PrecisionBloom;
+    ///
+    /// let filter = PrecisionBloom::with_capacity(100, 0.01);
+    /// assert_eq!(filter.contains_with_confidence(&"missing"), (false, 1.0));
+    /// ```
+    pub fn contains_with_confidence<T: Hash>(&self, item: &T) -> (bool, f64) {
+        if self.contains(item) {
+            (true, 1.0 - self.actual_false_positive_rate())
+        } else {
+            (false, 1.0)
+        }
+    }
+
+    /// Get a rough confidence that a positive answer for this item is real.
+    ///
+    /// Returns `1 - saturation()^num_hashes` if the item is reported present,
+    /// the chance that a random item would not land entirely on set bits, or
+    /// `None` if it is absent. Unlike
+    /// [`contains_with_confidence`](Self::contains_with_confidence), this uses
+    /// the measured fill rather than the tracked item count.
+    pub fn positive_confidence<T: Hash>(&self, item: &T) -> Option<f64> {
+        if !self.contains(item) {
+            return None;
+        }
+        Some(1.0 - self.saturation().powi(self.params.num_hashes as i32))
+    }
+
+    /// Check if an item might be in the bloom filter (alias for contains).
+    ///
+    /// This method is provided for clarity in some contexts.
+    #[inline]
+    pub fn may_contain<T: Hash>(&self, item: &T) -> bool {
+        self.contains(item)
+    }
+
+    /// Pre-fault the bit array's memory as an optional warm-up.
+    ///
+    /// Writes to every page of the bit array without changing its contents,
+    /// so a huge filter's memory is committed before latency-sensitive use
+    /// rather than on the first inserts.
+    pub fn prefault(&mut self) {
+        Arc::make_mut(&mut self.bits).prefault();
+    }
+
+    /// Convert into an immutable filter optimized for queries.
+    ///
+    /// The result answers membership identically but cannot be inserted into
+    /// or converted back; see [`OptimizedBloom`] for the tradeoffs.
+    pub fn into_optimized(self) -> OptimizedBloom {
+        let len = self.len();
+        let words = self.bits.as_words().to_vec().into_boxed_slice();
+        OptimizedBloom::new(words, self.hash_strategy, self.params, len)
+    }
+
+    /// Take a cheap read-only snapshot of the filter.
+    ///
+    /// The snapshot shares the bit array with this filter instead of copying
+    /// it. The next write to this filter copies the bits first, so the
+    /// snapshot keeps reflecting the state at the time it was taken.
+    ///
+    /// # Example
+    /// ```
+    /// use bloom_filter_rs::PrecisionBloom;
+    ///
+    /// let mut filter = PrecisionBloom::with_capacity(100, 0.01);
+    /// filter.insert(&"before");
+    ///
+    /// let snapshot = filter.snapshot();
+    /// filter.insert(&"after");
+    ///
+    /// assert!(snapshot.contains(&"before"));
+    /// assert!(!snapshot.contains(&"after"));
+    /// ```
+    pub fn snapshot(&self) -> Arc<PrecisionBloom> {
+        Arc::new(self.clone())
+    }
+
+    /// Clear all items from the filter.
+    ///
+    /// Resets the filter to its initial empty state.
+    pub fn clear(&mut self) {
+        // Bits shared with a snapshot are replaced rather than copied and cleared
+        match Arc::get_mut(&mut self.bits) {
+            Some(bits) => bits.clear(),
+            None => self.bits = Arc::new(BitArray::new(self.params.num_bits)),
+        }
+        self.tracker.reset();
+    }
+
+    /// Get the number of items inserted into the filter.
     ///
     /// Note: This is tracked by the filter, not guaranteed to be exact
     /// if the same item is inserted multiple times.
@@ -151,245 +1310,2330 @@ impl PrecisionBloom {
         self.len() == 0
     }
 
-    /// Get the capacity (expected number of items) of the filter.
-    pub fn capacity(&self) -> usize {
-        self.params.expected_items
+    /// Get the capacity (expected number of items) of the filter.
+    pub fn capacity(&self) -> usize {
+        self.params.expected_items
+    }
+
+    /// Get the number of bits in the filter.
+    pub fn num_bits(&self) -> usize {
+        self.params.num_bits
+    }
+
+    /// Get the number of hash functions used.
+    pub fn num_hashes(&self) -> usize {
+        self.params.num_hashes
+    }
+
+    /// Get the heap memory used by the bit array, in bytes.
+    ///
+    /// Bits are stored in 64-bit words, so this is `num_bits` rounded up to a
+    /// multiple of 64, divided by 8.
+    pub fn memory_usage(&self) -> usize {
+        std::mem::size_of_val(self.bits.as_words())
+    }
+
+    /// Estimate how many times more memory a `HashSet` of the inserted items
+    /// would need than this filter.
+    ///
+    /// The set is estimated as `len() * (avg_item_bytes + 16)`, where the
+    /// extra bytes approximate per-entry table overhead.
+    ///
+    /// # Example
+    /// ```
+    /// use bloom_filter_rs::PrecisionBloom;
+    ///
+    /// let mut filter = PrecisionBloom::with_capacity(10_000, 0.01);
+    /// for i in 0..10_000u64 {
+    ///     filter.insert(&i);
+    /// }
+    ///
+    /// // Storing the 8-byte keys directly would take ~20x the memory
+    /// assert!(filter.memory_savings_vs_hashset(8) > 10.0);
+    /// ```
+    pub fn memory_savings_vs_hashset(&self, avg_item_bytes: usize) -> f64 {
+        let hashset_bytes = self.len() * (avg_item_bytes + HASHSET_ENTRY_OVERHEAD);
+        hashset_bytes as f64 / self.memory_usage() as f64
+    }
+
+    /// Get the theoretical false positive rate.
+    pub fn false_positive_rate(&self) -> f64 {
+        self.tracker.theoretical_fpr()
+    }
+
+    /// Get the actual false positive rate based on current fill level.
+    pub fn actual_false_positive_rate(&self) -> f64 {
+        self.tracker.actual_fpr()
+    }
+
+    /// Get the theoretical false positive rate after `additional` more inserts.
+    ///
+    /// Evaluated at `len() + additional` items, so a batch can be checked
+    /// against an FPR budget before it is inserted.
+    pub fn fpr_after_inserting(&self, additional: usize) -> f64 {
+        self.params
+            .actual_fpr(self.len().saturating_add(additional))
+    }
+
+    /// Check whether `lookahead_items` more inserts would reach `cliff_fpr`.
+    ///
+    /// The false positive rate climbs slowly at first and then sharply as
+    /// the filter saturates, so this gives warning to rebuild before accuracy
+    /// collapses.
+    pub fn is_near_fpr_cliff(&self, lookahead_items: usize, cliff_fpr: f64) -> bool {
+        self.fpr_after_inserting(lookahead_items) >= cliff_fpr
+    }
+
+    /// Forecast how long until the false positive rate crosses `max_fpr`.
+    ///
+    /// The remaining headroom comes from [`BloomParameters::max_items_for_fpr`];
+    /// dividing it by `recent_insert_rate` gives the time left, in the same
+    /// units as the rate. Returns `Some(0.0)` if the rate is already exceeded,
+    /// and `None` if the filter will never get there: the insert rate is not
+    /// positive, or `max_fpr` is 1 or more.
+    pub fn forecast_capacity_exhaustion(
+        &self,
+        recent_insert_rate: f64,
+        max_fpr: f64,
+    ) -> Option<f64> {
+        let max_items = self.params.max_items_for_fpr(max_fpr);
+        if self.len() >= max_items {
+            return Some(0.0);
+        }
+        if recent_insert_rate <= 0.0 || max_items == usize::MAX {
+            return None;
+        }
+
+        Some((max_items - self.len()) as f64 / recent_insert_rate)
+    }
+
+    /// Get the expected number of absent-item queries per false positive.
+    ///
+    /// This is the reciprocal of [`actual_false_positive_rate`](Self::actual_false_positive_rate),
+    /// or infinity for an empty filter, which never reports a false positive.
+    pub fn expected_queries_per_false_positive(&self) -> f64 {
+        let fpr = self.actual_false_positive_rate();
+        if fpr <= 0.0 {
+            return f64::INFINITY;
+        }
+        1.0 / fpr
+    }
+
+    /// Get the saturation level of the filter (proportion of bits set).
+    ///
+    /// Returns a value between 0.0 (empty) and 1.0 (completely full).
+    pub fn saturation(&self) -> f64 {
+        self.bits.saturation()
+    }
+
+    /// Get the expected number of new bits an average insert sets.
+    ///
+    /// Computed as `num_hashes * (1 - saturation())`: each of the `k` positions
+    /// lands on an unset bit with probability equal to the unset fraction. This
+    /// approaches 0 as the filter fills, so later inserts write less.
+    pub fn expected_new_bits_per_insert(&self) -> f64 {
+        self.params.num_hashes as f64 * (1.0 - self.saturation())
+    }
+
+    /// Get the change in set bits needed to move the achieved false positive
+    /// rate to `target_fpr`.
+    ///
+    /// The achieved rate for `X` set bits is `(X / m)^k`, so the target is
+    /// reached at `X = m * target_fpr^(1/k)`. The result is that count minus
+    /// [`count_ones`](BitArray::count_ones), rounded to the nearest bit. A
+    /// negative value means bits would have to be cleared, which a plain bloom
+    /// filter cannot do, but still shows how far past the target it is.
+    ///
+    /// # Panics
+    /// Panics if target_fpr is not between 0 and 1
+    pub fn bits_to_target_fpr(&self, target_fpr: f64) -> i64 {
+        assert!(
+            (0.0..=1.0).contains(&target_fpr),
+            "target_fpr must be between 0 and 1"
+        );
+
+        let m = self.params.num_bits as f64;
+        let k = self.params.num_hashes as f64;
+        let target_ones = (m * target_fpr.powf(1.0 / k)).round() as i64;
+
+        target_ones - self.bits.count_ones() as i64
+    }
+
+    /// Set random bits until the filter reaches the target saturation.
+    ///
+    /// This is intended for benchmarking and testing the false positive rate
+    /// at controlled fill levels: it sets distinct random bits directly and
+    /// does not record any insertions, so `len()` is unchanged. Does nothing
+    /// if the filter is already at or above the target.
+    ///
+    /// # Panics
+    /// Panics if target is not between 0 and 1
+    #[cfg(feature = "rand")]
+    pub fn fill_to_saturation(&mut self, target: f64, rng: &mut impl Rng) {
+        assert!(
+            (0.0..=1.0).contains(&target),
+            "target must be between 0 and 1"
+        );
+
+        let num_bits = self.params.num_bits;
+        let target_ones = (target * num_bits as f64).round() as usize;
+        let mut ones = self.bits.count_ones();
+        let bits = Arc::make_mut(&mut self.bits);
+
+        while ones < target_ones {
+            let index = rng.gen_range(0..num_bits);
+            if !bits.get(index) {
+                bits.set(index);
+                ones += 1;
+            }
+        }
+    }
+
+    /// Estimate the false positive rate empirically by probing random keys.
+    ///
+    /// Generates `trials` random 16-byte strings, which are vanishingly unlikely
+    /// to have been inserted, and returns the fraction the filter reports as
+    /// present. Unlike the closed-form rate, this reflects the real hash behavior
+    /// on the actual bit array.
+    ///
+    /// # Panics
+    /// Panics if trials is 0
+    #[cfg(feature = "rand")]
+    pub fn empirical_fpr_estimate(&self, trials: usize, rng: &mut impl Rng) -> f64 {
+        assert!(trials > 0, "trials must be greater than 0");
+
+        let positives = (0..trials)
+            .filter(|_| self.contains(&rng.gen::<[u8; 16]>()))
+            .count();
+
+        positives as f64 / trials as f64
+    }
+
+    /// Count the distinct bit positions an item occupies.
+    ///
+    /// Double hashing can map two of an item's `k` hashes to the same bit, so
+    /// an item may occupy fewer than `num_hashes` positions. Consistently low
+    /// values across many keys point to a hashing problem.
+    pub fn distinct_positions<T: Hash>(&self, item: &T) -> usize {
+        let mut indices = self.hash_strategy.hash_indices(item);
+        indices.sort_unstable();
+        indices.dedup();
+        indices.len()
+    }
+
+    /// Get the hash count that would be optimal for the items inserted so far.
+    ///
+    /// Computed as `round((num_bits / len()) * ln 2)`, at least 1. Once a filter
+    /// fills past its planned capacity this drops below
+    /// [`num_hashes`](Self::num_hashes), showing how far the configuration has
+    /// drifted. Returns `num_hashes` for an empty filter.
+    pub fn current_optimal_hashes(&self) -> usize {
+        if self.is_empty() {
+            return self.params.num_hashes;
+        }
+
+        let ratio = self.params.num_bits as f64 / self.len() as f64;
+        ((ratio * f64::ln(2.0)).round() as usize).max(1)
+    }
+
+    /// Get the average number of distinct positions per item over a sample.
+    ///
+    /// Equals `num_hashes` when no item's hashes collide. Returns
+    /// `num_hashes` for an empty sample.
+    pub fn effective_num_hashes<T: Hash>(&self, sample: &[T]) -> f64 {
+        if sample.is_empty() {
+            return self.params.num_hashes as f64;
+        }
+
+        let total: usize = sample
+            .iter()
+            .map(|item| self.distinct_positions(item))
+            .sum();
+        total as f64 / sample.len() as f64
+    }
+
+    /// Get the false positive rate at the current fill level using the
+    /// effective hash count measured over a sample.
+    ///
+    /// Formula: p = (1 - e^(-k'n/m))^k', where k' is
+    /// [`effective_num_hashes`](Self::effective_num_hashes). This accounts for
+    /// double-hashing collisions that the theoretical rate ignores.
+    pub fn effective_fpr<T: Hash>(&self, sample: &[T]) -> f64 {
+        let k = self.effective_num_hashes(sample);
+        let n = self.len() as f64;
+        let m = self.params.num_bits as f64;
+
+        (1.0 - f64::exp(-k * n / m)).powf(k)
+    }
+
+    /// Preview the bits an insert would newly set, without modifying the filter.
+    ///
+    /// Returns the sorted, distinct indices that are currently unset and would
+    /// be set by inserting `item`. An empty result means the item is already
+    /// probably present.
+    pub fn insert_preview<T: Hash>(&self, item: &T) -> Vec<usize> {
+        let mut indices: Vec<usize> = self
+            .hash_strategy
+            .hash_indices(item)
+            .into_iter()
+            .filter(|&index| !self.bits.get(index))
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+    }
+
+    /// Get the storage location of each of an item's bit positions.
+    ///
+    /// Returns one `(word_index, bit_within_word)` pair per hash function, as
+    /// computed by [`BitArray::locate`].
+    pub fn bit_storage_map<T: Hash>(&self, item: &T) -> Vec<(usize, usize)> {
+        self.hash_strategy
+            .hash_indices(item)
+            .into_iter()
+            .map(BitArray::locate)
+            .collect()
+    }
+
+    /// Measure how much a reference hash backend's placements overlap this filter's.
+    ///
+    /// For each item, the bit positions chosen by this filter are compared with
+    /// those a different [`HashBackend`] would choose for the same geometry,
+    /// and the fraction of shared positions is averaged over all items.
+    /// Independent backends overlap only at chance levels (about `k / m`),
+    /// which is what a design combining two backends relies on.
+    pub fn placement_overlap<T: Hash>(&self, items: &[T]) -> f64 {
+        if items.is_empty() {
+            return 0.0;
+        }
+
+        let reference_backend = match self.hash_strategy.backend() {
+            HashBackend::AhashSeahash | HashBackend::Keyed { .. } => HashBackend::Seahash,
+            HashBackend::Seahash => HashBackend::AhashSeahash,
+        };
+        let reference = HashStrategy::with_backend(
+            self.params.num_hashes,
+            self.params.num_bits,
+            reference_backend,
+        )
+        .with_scheme(self.hash_strategy.scheme());
+
+        let total: f64 = items
+            .iter()
+            .map(|item| {
+                let current = self.hash_strategy.hash_indices(item);
+                let shared = reference
+                    .hash_indices(item)
+                    .iter()
+                    .filter(|index| current.contains(index))
+                    .count();
+                shared as f64 / current.len() as f64
+            })
+            .sum();
+
+        total / items.len() as f64
+    }
+
+    /// Get the normalized entropy of the set-bit distribution.
+    ///
+    /// Set bits are bucketed into regions of the bit array and the Shannon
+    /// entropy of the bucket counts is normalized to `[0, 1]`. A healthy hash
+    /// spreads bits evenly and scores near 1.0; a low score means bits are
+    /// clustered, which points to a broken hash.
+    pub fn bit_entropy(&self) -> f64 {
+        self.bits.region_entropy(ENTROPY_REGIONS)
+    }
+
+    /// Get the start and length of the longest run of unset bits.
+    ///
+    /// A sparse filter has a long run; a surprisingly long run in a filter
+    /// that should be full points to a region the hash never reaches.
+    pub fn longest_zero_run(&self) -> (usize, usize) {
+        self.bits.longest_zero_run()
+    }
+
+    /// Get the probability that the next inserted item collides entirely with
+    /// bits that are already set.
+    ///
+    /// Computed as `saturation()^num_hashes`, this is the chance that `insert`
+    /// reports the item as possibly present even though it is new.
+    pub fn insert_collision_probability(&self) -> f64 {
+        self.saturation().powi(self.params.num_hashes as i32)
+    }
+
+    /// Estimate the number of distinct items in the filter from its bits.
+    ///
+    /// Unlike `len()`, this is not inflated by duplicate insertions. Returns
+    /// infinity if every bit is set.
+    pub fn estimated_cardinality(&self) -> f64 {
+        self.params.estimate_items(self.bits.count_ones())
+    }
+
+    /// Get the tracked insert count alongside the bit-derived cardinality estimate.
+    ///
+    /// Returns `(len(), estimated_cardinality())`. A tracked count well above
+    /// the estimate means many inserts were duplicates.
+    pub fn tracked_vs_estimated(&self) -> (usize, f64) {
+        (self.len(), self.estimated_cardinality())
+    }
+
+    /// Seed the tracked insert count from [`estimated_cardinality`](Self::estimated_cardinality).
+    ///
+    /// Useful after [`from_parts`](Self::from_parts), where the true count is
+    /// unknown. The query count is left unchanged. A saturated filter is
+    /// seeded with its capacity. Returns the count that was set.
+    pub fn estimate_and_set_item_count(&mut self) -> usize {
+        let estimate = self.estimated_cardinality();
+        let count = if estimate.is_finite() {
+            estimate.round() as usize
+        } else {
+            self.capacity()
+        };
+
+        self.tracker
+            .set_counts(count, self.tracker.queries_performed());
+        count
+    }
+
+    /// Suggest parameters for rebuilding the filter with room to grow.
+    ///
+    /// Sizes for `estimated_cardinality() * (1 + headroom)` items at the
+    /// original target false positive rate. If the filter is fully saturated
+    /// the tracked insert count is used instead of the estimate.
+    ///
+    /// # Arguments
+    /// * `headroom` - Extra capacity as a fraction, e.g. 0.5 for 50% growth room
+    ///
+    /// # Panics
+    /// Panics if headroom is negative
+    pub fn suggested_rebuild_params(&self, headroom: f64) -> BloomParameters {
+        assert!(headroom >= 0.0, "headroom must not be negative");
+
+        let estimate = self.estimated_cardinality();
+        let base = if estimate.is_finite() {
+            estimate
+        } else {
+            self.len() as f64
+        };
+
+        let items = (base * (1.0 + headroom)).ceil() as usize;
+        BloomParameters::from_item_count(items.max(1), self.params.false_positive_rate)
+    }
+
+    /// Get the fraction of recent `insert_if_absent` calls that saw a new item.
+    ///
+    /// A value near 0.0 means the stream is mostly duplicates, which may mean
+    /// work could be skipped upstream.
+    pub fn recent_novelty_rate(&self) -> f64 {
+        self.tracker.recent_novelty_rate()
+    }
+
+    /// Keep a history of up to `capacity` FPR samples.
+    ///
+    /// Samples are taken by [`record_fpr_sample`](Self::record_fpr_sample).
+    /// A capacity of 0 disables history.
+    pub fn enable_fpr_history(&mut self, capacity: usize) {
+        self.tracker.set_history_capacity(capacity);
+    }
+
+    /// Record the current fill level and FPR in the history.
+    ///
+    /// Does nothing unless history was enabled with
+    /// [`enable_fpr_history`](Self::enable_fpr_history).
+    pub fn record_fpr_sample(&mut self) {
+        self.tracker.snapshot();
+    }
+
+    /// Get the recorded FPR samples, oldest first.
+    pub fn fpr_history(&self) -> &[FprSample] {
+        self.tracker.fpr_history()
+    }
+
+    /// Get the policy for inserts past the filter's capacity.
+    pub fn overfill_behavior(&self) -> OverfillBehavior {
+        self.overfill_behavior
+    }
+
+    /// Check if the filter has exceeded its expected capacity.
+    pub fn is_overfilled(&self) -> bool {
+        self.tracker.is_overfilled()
+    }
+
+    /// Get a status summary of the filter.
+    pub fn status(&self) -> String {
+        self.tracker.status_summary()
+    }
+
+    /// Report the hash backend, index scheme and versions behind this filter.
+    ///
+    /// # Example
+    /// ```
+    /// use bloom_filter_rs::PrecisionBloom;
+    ///
+    /// let filter = PrecisionBloom::with_capacity(100, 0.01);
+    /// assert_eq!(filter.provenance().backend, "ahash+seahash");
+    /// ```
+    pub fn provenance(&self) -> Provenance {
+        Provenance {
+            backend: self.hash_strategy.backend().name(),
+            scheme: self.hash_strategy.scheme().name(),
+            format_version: FORMAT_VERSION,
+            crate_version: env!("CARGO_PKG_VERSION"),
+        }
+    }
+
+    /// Get a stable 128-bit fingerprint of the filter, e.g. for use as a cache key.
+    ///
+    /// The high 64 bits hash the bit array and the low 64 bits hash the
+    /// parameters, hash backend and scheme, both with unkeyed seahash. Items
+    /// are placed with fixed keys, so the value is the same across processes
+    /// and runs, subject to the ahash caveats on [`HashBackend::Keyed`].
+    /// Filters built from the same set with the same configuration share a
+    /// fingerprint. Like membership itself this is
+    /// approximate: different sets can produce identical bits and so collide.
+    /// Item and query counts are not included.
+    pub fn fingerprint(&self) -> u128 {
+        let mut content = seahash::SeaHasher::new();
+        for &word in self.bits.as_words() {
+            content.write_u64(word);
+        }
+
+        let mut config = seahash::SeaHasher::new();
+        config.write_u64(self.params.num_bits as u64);
+        config.write_u64(self.params.num_hashes as u64);
+        config.write_u64(self.params.expected_items as u64);
+        config.write_u64(self.params.false_positive_rate.to_bits());
+        config.write(self.hash_strategy.backend().name().as_bytes());
+        config.write(self.hash_strategy.scheme().name().as_bytes());
+
+        (u128::from(content.finish()) << 64) | u128::from(config.finish())
+    }
+
+    /// Get a snapshot of the filter's metrics for export.
+    pub fn metrics(&self) -> BloomMetrics {
+        BloomMetrics {
+            items_inserted: self.len(),
+            num_bits: self.params.num_bits,
+            num_hashes: self.params.num_hashes,
+            saturation_ratio: self.saturation(),
+            theoretical_fpr: self.false_positive_rate(),
+            actual_fpr: self.actual_false_positive_rate(),
+            memory_bytes: self.memory_usage(),
+            is_overfilled: self.is_overfilled(),
+        }
+    }
+
+    /// Validate the filter against the set of items that were inserted.
+    ///
+    /// Every item in `truth` is checked with `contains`; any item reported
+    /// absent is a false negative, which indicates the filter was not
+    /// populated with the items it is expected to hold.
+    pub fn validate_against<T: Hash + Eq + Clone>(&self, truth: &HashSet<T>) -> ValidationReport {
+        let false_negatives = truth.iter().filter(|item| !self.contains(*item)).count();
+
+        ValidationReport {
+            items_checked: truth.len(),
+            false_negatives,
+        }
+    }
+
+    /// Check that the bit array is consistent with the tracked insert count.
+    ///
+    /// Inserting `n` items into `m` bits with `k` hashes is expected to set
+    /// about `1 - e^(-kn/m)` of the bits. The audit fails if the observed
+    /// saturation exceeds that by more than `tolerance` (an absolute fraction
+    /// of the array), which indicates bits were set without being counted,
+    /// for example by a tampered or corrupted serialized filter.
+    ///
+    /// Saturation below the prediction is not an error, since inserting the
+    /// same item twice counts twice but sets no new bits.
+    ///
+    /// # Example
+    /// ```
+    /// use bloom_filter_rs::PrecisionBloom;
+    ///
+    /// let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+    /// for i in 0..500 {
+    ///     filter.insert(&i);
+    /// }
+    /// assert!(filter.audit_consistency(0.05).is_ok());
+    /// ```
+    pub fn audit_consistency(&self, tolerance: f64) -> Result<(), String> {
+        let m = self.params.num_bits as f64;
+        let k = self.params.num_hashes as f64;
+        let n = self.len() as f64;
+
+        let expected = 1.0 - f64::exp(-k * n / m);
+        let observed = self.saturation();
+
+        if observed - expected > tolerance {
+            return Err(format!(
+                "saturation {:.4} exceeds {:.4} expected for {} items by more than {}",
+                observed,
+                expected,
+                self.len(),
+                tolerance
+            ));
+        }
+        Ok(())
+    }
+
+    /// Produce a stable, detailed text dump of the filter for snapshot tests.
+    ///
+    /// Lists the parameters, insert count and popcount, followed by the first
+    /// and last few words of the bit array in hex. The format is fixed, so
+    /// filters built from the same items in the same order dump identically.
+    pub fn debug_dump(&self) -> String {
+        let words = self.bits.as_words();
+        let head = words.len().min(DUMP_WORDS);
+        let tail = words.len().saturating_sub(DUMP_WORDS).max(head);
+
+        let hex = |words: &[u64]| {
+            words
+                .iter()
+                .map(|word| format!("{:016x}", word))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        format!(
+            "num_bits={}\nnum_hashes={}\nexpected_items={}\nfalse_positive_rate={}\n\
+             items_inserted={}\npopcount={}\nwords={}\nhead=[{}]\ntail=[{}]\n",
+            self.params.num_bits,
+            self.params.num_hashes,
+            self.params.expected_items,
+            self.params.false_positive_rate,
+            self.len(),
+            self.bits.count_ones(),
+            words.len(),
+            hex(&words[..head]),
+            hex(&words[tail..]),
+        )
+    }
+
+    /// Get the bit positions that one item sets and the other does not.
+    ///
+    /// Returns the sorted symmetric difference of the two items' hash
+    /// positions. An empty result means the filter cannot tell the items
+    /// apart: inserting either makes the other a guaranteed false positive.
+    pub fn distinguishing_positions<T: Hash>(&self, a: &T, b: &T) -> Vec<usize> {
+        let a: BTreeSet<usize> = self.hash_strategy.hash_indices(a).into_iter().collect();
+        let b: BTreeSet<usize> = self.hash_strategy.hash_indices(b).into_iter().collect();
+        a.symmetric_difference(&b).copied().collect()
+    }
+
+    /// Check whether this filter can be unioned or intersected with another.
+    ///
+    /// Requires [`BloomParameters::compatible_with`] and the same hash
+    /// backend and scheme, so an item maps to the same bits in both filters.
+    pub fn is_union_compatible(&self, other: &PrecisionBloom) -> bool {
+        self.params.compatible_with(&other.params)
+            && self.hash_strategy.backend() == other.hash_strategy.backend()
+            && self.hash_strategy.scheme() == other.hash_strategy.scheme()
+    }
+
+    /// Merge another filter's items into this one.
+    ///
+    /// The result reports every item either filter held. Item counts are
+    /// added, which overcounts items present in both.
+    ///
+    /// # Errors
+    /// Returns `BloomError::IncompatibleFilters` unless
+    /// [`is_union_compatible`](Self::is_union_compatible) holds.
+    pub fn union_with(&mut self, other: &PrecisionBloom) -> Result<(), BloomError> {
+        if !self.is_union_compatible(other) {
+            return Err(BloomError::IncompatibleFilters);
+        }
+
+        Arc::make_mut(&mut self.bits).or_with(&other.bits);
+        self.tracker.set_counts(
+            self.tracker.items_inserted() + other.tracker.items_inserted(),
+            self.tracker.queries_performed(),
+        );
+        Ok(())
+    }
+
+    /// Merge many filters into a new one in a single pass.
+    ///
+    /// Equivalent to chaining [`union_with`](Self::union_with) from a copy of
+    /// the first filter, but only the result's bit array is allocated: every
+    /// input's words are ORed straight into it. The result takes the first
+    /// filter's hashing configuration and overfill behavior, and its item count
+    /// is the sum of the inputs'.
+    ///
+    /// # Errors
+    /// Returns `BloomError::IncompatibleFilters` unless every filter is
+    /// [union-compatible](Self::is_union_compatible) with the first.
+    ///
+    /// # Panics
+    /// Panics if `filters` is empty
+    pub fn union_many(filters: &[PrecisionBloom]) -> Result<PrecisionBloom, BloomError> {
+        let first = filters.first().expect("filters must not be empty");
+        if !filters
+            .iter()
+            .all(|filter| first.is_union_compatible(filter))
+        {
+            return Err(BloomError::IncompatibleFilters);
+        }
+
+        let mut result = Self::empty_like(first);
+        let bits = Arc::make_mut(&mut result.bits);
+        for filter in filters {
+            bits.or_with(&filter.bits);
+        }
+
+        let items = filters.iter().map(|filter| filter.len()).sum();
+        result.tracker.set_counts(items, 0);
+        Ok(result)
+    }
+
+    /// Get the parameters of this filter.
+    pub fn parameters(&self) -> &BloomParameters {
+        &self.params
+    }
+
+    /// Convert the filter's bits into a `BitVec`.
+    ///
+    /// Bit `i` of the result is bit `i` of the filter, using the same
+    /// LSB-first `u64` layout as [`BitArray`].
+    #[cfg(feature = "bitvec")]
+    pub fn to_bitvec(&self) -> bitvec::vec::BitVec<u64, bitvec::order::Lsb0> {
+        let mut bv = bitvec::vec::BitVec::from_vec(self.bits.as_words().to_vec());
+        bv.truncate(self.params.num_bits);
+        bv
+    }
+
+    /// Create a filter from a `BitVec` and the parameters it was built with.
+    ///
+    /// The insert count is not known and starts at zero.
+    ///
+    /// # Panics
+    /// Panics if the parameters are invalid or the `BitVec` length differs from `num_bits`
+    #[cfg(feature = "bitvec")]
+    pub fn from_bitvec(
+        mut bv: bitvec::vec::BitVec<u64, bitvec::order::Lsb0>,
+        params: BloomParameters,
+    ) -> Self {
+        assert_eq!(
+            bv.len(),
+            params.num_bits,
+            "bit vector length must equal num_bits"
+        );
+
+        // Clear any stale bits past the end so they don't leak into the words
+        bv.set_uninitialized(false);
+
+        params.validate().expect("Invalid parameters");
+        Self::with_bits(params, BitArray::from_words(bv.into_vec(), params.num_bits))
+    }
+
+    /// Export the set bits as inclusive `(start, end)` ranges of consecutive indices.
+    ///
+    /// Ranges are sorted and non-overlapping. A sparse filter compresses to a
+    /// handful of ranges; rebuild it with [`from_ranges`](Self::from_ranges).
+    pub fn occupied_ranges(&self) -> Vec<(usize, usize)> {
+        let mut ranges = Vec::new();
+        let mut start = None;
+
+        for index in 0..self.params.num_bits {
+            match (self.bits.get(index), start) {
+                (true, None) => start = Some(index),
+                (false, Some(first)) => {
+                    ranges.push((first, index - 1));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(first) = start {
+            ranges.push((first, self.params.num_bits - 1));
+        }
+
+        ranges
+    }
+
+    /// Rebuild a filter from ranges produced by [`occupied_ranges`](Self::occupied_ranges).
+    ///
+    /// As with [`from_parts`](Self::from_parts), the tracked insert count
+    /// starts at zero.
+    ///
+    /// # Errors
+    /// Returns `BloomError::InvalidFormat` if the parameters are invalid or a
+    /// range is reversed or extends past `params.num_bits`.
+    ///
+    /// # Example
+    /// ```
+    /// use bloom_filter_rs::PrecisionBloom;
+    ///
+    /// let mut filter = PrecisionBloom::with_capacity(100, 0.01);
+    /// filter.insert(&"hello");
+    ///
+    /// let ranges = filter.occupied_ranges();
+    /// let restored = PrecisionBloom::from_ranges(*filter.parameters(), &ranges).unwrap();
+    /// assert!(restored.contains(&"hello"));
+    /// ```
+    pub fn from_ranges(
+        params: BloomParameters,
+        ranges: &[(usize, usize)],
+    ) -> Result<Self, BloomError> {
+        let mut filter = Self::from_parts(params, BitArray::new(params.num_bits))?;
+        let bits = Arc::make_mut(&mut filter.bits);
+
+        for &(start, end) in ranges {
+            if start > end || end >= params.num_bits {
+                return Err(BloomError::InvalidFormat(format!(
+                    "range ({}, {}) is invalid for {} bits",
+                    start, end, params.num_bits
+                )));
+            }
+            for index in start..=end {
+                bits.set(index);
+            }
+        }
+
+        Ok(filter)
+    }
+
+    /// Get the exact number of bytes [`to_bytes`](Self::to_bytes) will produce.
+    ///
+    /// This is the fixed header plus eight bytes per bit array word, so it can
+    /// be checked against a size limit before serializing.
+    pub fn serialized_size(&self) -> usize {
+        HEADER_LEN + self.bits.as_words().len() * 8 + CHECKSUM_LEN
+    }
+
+    /// Serialize the filter into a compact binary format.
+    ///
+    /// The layout is a fixed header (magic, format version, `num_bits`,
+    /// `num_hashes`, `expected_items`, the target false positive rate, the
+    /// tracker's insert and query counts, the hash backend, hash scheme and
+    /// overfill behavior, and the backend's keys if it has any) followed by
+    /// the bit array words and a CRC32 of everything before it, all
+    /// little-endian.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let words = self.bits.as_words();
+        let mut bytes = Vec::with_capacity(self.serialized_size());
+
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(FORMAT_VERSION);
+        bytes.extend_from_slice(&(self.params.num_bits as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.params.num_hashes as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.params.expected_items as u64).to_le_bytes());
+        bytes.extend_from_slice(&self.params.false_positive_rate.to_le_bytes());
+        bytes.extend_from_slice(&(self.tracker.items_inserted() as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.tracker.queries_performed() as u64).to_le_bytes());
+
+        let backend = self.hash_strategy.backend();
+        bytes.push(backend.tag());
+        bytes.push(self.hash_strategy.scheme().tag());
+        bytes.push(self.overfill_behavior.tag());
+        let (ahash_keys, seahash_seeds) = match backend {
+            HashBackend::Keyed {
+                ahash_keys,
+                seahash_seeds,
+            } => (ahash_keys, seahash_seeds),
+            _ => ([0; 4], [0; 4]),
+        };
+        for key in ahash_keys.iter().chain(&seahash_seeds) {
+            bytes.extend_from_slice(&key.to_le_bytes());
+        }
+
+        for word in words {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+
+        let checksum = crc32fast::hash(&bytes);
+        bytes.extend_from_slice(&checksum.to_le_bytes());
+
+        bytes
+    }
+
+    /// Deserialize a filter previously produced by [`to_bytes`](Self::to_bytes).
+    ///
+    /// The hash backend (with its keys), hash scheme and overfill behavior are
+    /// restored from the header. Input written before they were stored
+    /// (format version 3) is restored with the defaults, and input written
+    /// before checksums were added (format version 2) is accepted without
+    /// verification.
+    ///
+    /// # Errors
+    /// Returns `BloomError::InvalidFormat` if the magic, version, parameters
+    /// or length of the input are not valid, and `BloomError::ChecksumMismatch`
+    /// if the data does not match its checksum.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BloomError> {
+        let header = Self::parse_header(bytes)?;
+        let params = header.params;
+
+        let checksum_len = Self::checksum_len(bytes[MAGIC.len()]);
+        let payload_len = bytes.len() - header.len;
+        Self::check_payload_len(&params, payload_len.saturating_sub(checksum_len))?;
+
+        let (data, stored) = bytes.split_at(bytes.len() - checksum_len);
+        if checksum_len > 0 {
+            let stored = u32::from_le_bytes(stored.try_into().expect("checksum is 4 bytes"));
+            let computed = crc32fast::hash(data);
+            if stored != computed {
+                return Err(BloomError::ChecksumMismatch { stored, computed });
+            }
+        }
+        let payload = &data[header.len..];
+
+        let words = payload
+            .chunks_exact(8)
+            .map(|chunk| {
+                let mut buf = [0u8; 8];
+                buf.copy_from_slice(chunk);
+                u64::from_le_bytes(buf)
+            })
+            .collect();
+
+        let mut filter = Self::from_parts(params, BitArray::from_words(words, params.num_bits))?;
+        filter.hash_strategy =
+            HashStrategy::with_backend(params.num_hashes, params.num_bits, header.backend)
+                .with_scheme(header.scheme);
+        filter.overfill_behavior = header.overfill_behavior;
+        filter
+            .tracker
+            .set_counts(header.items_inserted, header.queries_performed);
+        Ok(filter)
+    }
+
+    /// Parse the binary header at the start of `bytes`.
+    ///
+    /// Headers older than the current format version get the default hash
+    /// backend, scheme and overfill behavior.
+    fn parse_header(bytes: &[u8]) -> Result<Header, BloomError> {
+        if bytes.len() < BASE_HEADER_LEN {
+            return Err(BloomError::InvalidFormat(
+                "input shorter than header".to_string(),
+            ));
+        }
+        if &bytes[..MAGIC.len()] != MAGIC {
+            return Err(BloomError::InvalidFormat("bad magic bytes".to_string()));
+        }
+        let version = bytes[MAGIC.len()];
+        if ![
+            FORMAT_VERSION,
+            UNCONFIGURED_FORMAT_VERSION,
+            UNCHECKED_FORMAT_VERSION,
+        ]
+        .contains(&version)
+        {
+            return Err(BloomError::InvalidFormat(format!(
+                "unsupported format version {}",
+                version
+            )));
+        }
+        if version == FORMAT_VERSION && bytes.len() < HEADER_LEN {
+            return Err(BloomError::InvalidFormat(
+                "input shorter than header".to_string(),
+            ));
+        }
+
+        let field = |i: usize| {
+            let start = MAGIC.len() + 1 + i * 8;
+            let mut buf = [0u8; 8];
+            buf.copy_from_slice(&bytes[start..start + 8]);
+            buf
+        };
+
+        let params = BloomParameters {
+            num_bits: u64::from_le_bytes(field(0)) as usize,
+            num_hashes: u64::from_le_bytes(field(1)) as usize,
+            expected_items: u64::from_le_bytes(field(2)) as usize,
+            false_positive_rate: f64::from_le_bytes(field(3)),
+        };
+        params.validate().map_err(BloomError::InvalidFormat)?;
+        // Filters are clamped on construction, so `to_bytes` never writes this
+        if params.num_hashes > params.num_bits {
+            return Err(BloomError::InvalidFormat(format!(
+                "{} hashes exceed {} bits",
+                params.num_hashes, params.num_bits
+            )));
+        }
+
+        let mut header = Header {
+            params,
+            items_inserted: u64::from_le_bytes(field(4)) as usize,
+            queries_performed: u64::from_le_bytes(field(5)) as usize,
+            backend: HashBackend::default(),
+            scheme: DoubleHashScheme::default(),
+            overfill_behavior: OverfillBehavior::default(),
+            len: BASE_HEADER_LEN,
+        };
+        if version != FORMAT_VERSION {
+            return Ok(header);
+        }
+
+        let tags = &bytes[BASE_HEADER_LEN..BASE_HEADER_LEN + 3];
+        let key = |i: usize| {
+            let start = BASE_HEADER_LEN + tags.len() + i * 8;
+            let mut buf = [0u8; 8];
+            buf.copy_from_slice(&bytes[start..start + 8]);
+            u64::from_le_bytes(buf)
+        };
+        let ahash_keys = [key(0), key(1), key(2), key(3)];
+        let seahash_seeds = [key(4), key(5), key(6), key(7)];
+
+        let invalid_tag = |what: &str, tag: u8| {
+            BloomError::InvalidFormat(format!("unknown {} tag {}", what, tag))
+        };
+        header.backend = HashBackend::from_tag(tags[0], ahash_keys, seahash_seeds)
+            .ok_or_else(|| invalid_tag("hash backend", tags[0]))?;
+        header.scheme = DoubleHashScheme::from_tag(tags[1])
+            .ok_or_else(|| invalid_tag("hash scheme", tags[1]))?;
+        header.overfill_behavior = OverfillBehavior::from_tag(tags[2])
+            .ok_or_else(|| invalid_tag("overfill behavior", tags[2]))?;
+        header.len = HEADER_LEN;
+
+        Ok(header)
+    }
+
+    /// Get the length of the trailing checksum for a format version.
+    fn checksum_len(version: u8) -> usize {
+        if version == UNCHECKED_FORMAT_VERSION {
+            0
+        } else {
+            CHECKSUM_LEN
+        }
+    }
+
+    /// Update the CRC32 of the first `len` bytes of a file after the 8 bytes
+    /// at `offset` change from `old` to `new`.
+    ///
+    /// CRC32 is affine, so the change to the checksum depends only on the two
+    /// values and on how many bytes follow them, and no other bytes are read.
+    fn patch_checksum(checksum: u32, len: u64, offset: u64, old: [u8; 8], new: [u8; 8]) -> u32 {
+        let mut delta =
+            crc32fast::Hasher::new_with_initial(crc32fast::hash(&old) ^ crc32fast::hash(&new));
+        delta.combine(&crc32fast::Hasher::new_with_initial_len(
+            0,
+            len - offset - 8,
+        ));
+        checksum ^ delta.finalize()
+    }
+
+    /// Check that the bit data following the header has the expected length.
+    fn check_payload_len(params: &BloomParameters, len: usize) -> Result<(), BloomError> {
+        let num_words = params.num_bits.div_ceil(64);
+        if len != num_words * 8 {
+            return Err(BloomError::InvalidFormat(format!(
+                "expected {} bytes of bit data, found {}",
+                num_words * 8,
+                len
+            )));
+        }
+        Ok(())
+    }
+
+    /// Insert an item into a filter stored in a file by [`to_bytes`](Self::to_bytes),
+    /// without loading or rewriting the whole file.
+    ///
+    /// Only words that change are written back. The insert count in the
+    /// header is then updated with a single 8-byte write, after the bits, so
+    /// an interrupted insert never counts an item whose bits are missing.
+    /// Finally the trailing checksum is patched from the changed words alone,
+    /// so the cost does not grow with the file; an insert interrupted before
+    /// that step leaves a file that fails checksum verification. Files
+    /// without a checksum (format version 2) skip that step. The file is
+    /// synced before returning.
+    ///
+    /// The item is hashed with the backend and scheme stored in the header,
+    /// and the stored overfill behavior is honored, as if the filter had been
+    /// loaded with [`from_bytes`](Self::from_bytes).
+    ///
+    /// # Returns
+    /// Returns `true` if the item was definitely not in the filter before.
+    ///
+    /// # Errors
+    /// Returns `BloomError::Io` if the file cannot be read or written,
+    /// `BloomError::InvalidFormat` if it does not hold a valid filter, and
+    /// `BloomError::CapacityExceeded` if the filter is at capacity and its
+    /// overfill behavior is [`OverfillBehavior::Reject`]. The file is not
+    /// modified when `InvalidFormat` or `CapacityExceeded` is returned, but an
+    /// I/O error partway through can leave it partially updated.
+    pub fn insert_to_file<T: Hash>(path: impl AsRef<Path>, item: &T) -> Result<bool, BloomError> {
+        let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+
+        let mut header_bytes = Vec::with_capacity(HEADER_LEN);
+        (&mut file)
+            .take(HEADER_LEN as u64)
+            .read_to_end(&mut header_bytes)?;
+        let header = Self::parse_header(&header_bytes)?;
+        let params = header.params;
+
+        let file_len = file.metadata()?.len();
+        let checksum_len = Self::checksum_len(header_bytes[MAGIC.len()]);
+        let data_len = file_len.saturating_sub(checksum_len as u64);
+        Self::check_payload_len(&params, (data_len as usize).saturating_sub(header.len))?;
+
+        if header.overfill_behavior == OverfillBehavior::Reject
+            && header.items_inserted >= params.expected_items
+        {
+            return Err(BloomError::CapacityExceeded {
+                capacity: params.expected_items,
+            });
+        }
+
+        let strategy =
+            HashStrategy::with_backend(params.num_hashes, params.num_bits, header.backend)
+                .with_scheme(header.scheme);
+        let mut word_indices: Vec<(usize, usize)> = strategy
+            .hash_indices(item)
+            .into_iter()
+            .map(BitArray::locate)
+            .collect();
+        word_indices.sort_unstable();
+
+        let mut checksum = 0;
+        if checksum_len > 0 {
+            let mut buf = [0u8; CHECKSUM_LEN];
+            file.seek(SeekFrom::Start(data_len))?;
+            file.read_exact(&mut buf)?;
+            checksum = u32::from_le_bytes(buf);
+        }
+
+        let mut was_absent = false;
+        for group in word_indices.chunk_by(|a, b| a.0 == b.0) {
+            let offset = (header.len + group[0].0 * 8) as u64;
+
+            let mut buf = [0u8; 8];
+            file.seek(SeekFrom::Start(offset))?;
+            file.read_exact(&mut buf)?;
+            let word = u64::from_le_bytes(buf);
+
+            let updated = group.iter().fold(word, |w, &(_, bit)| w | (1u64 << bit));
+            if updated != word {
+                was_absent = true;
+                checksum =
+                    Self::patch_checksum(checksum, data_len, offset, buf, updated.to_le_bytes());
+                file.seek(SeekFrom::Start(offset))?;
+                file.write_all(&updated.to_le_bytes())?;
+            }
+        }
+
+        let items = (header.items_inserted as u64 + 1).to_le_bytes();
+        file.seek(SeekFrom::Start(ITEMS_FIELD_OFFSET as u64))?;
+        file.write_all(&items)?;
+
+        if checksum_len > 0 {
+            let old_items = (header.items_inserted as u64).to_le_bytes();
+            checksum = Self::patch_checksum(
+                checksum,
+                data_len,
+                ITEMS_FIELD_OFFSET as u64,
+                old_items,
+                items,
+            );
+            file.seek(SeekFrom::Start(data_len))?;
+            file.write_all(&checksum.to_le_bytes())?;
+        }
+        file.sync_data()?;
+
+        Ok(was_absent)
+    }
+
+    /// Encode the filter's binary format as a standard padded base64 string.
+    ///
+    /// Suitable for embedding small filters in text formats such as JSON or
+    /// YAML config. The string records the hash backend and its keys, and the
+    /// default backend's keys are fixed, so it can be generated once and
+    /// loaded by any process.
+    ///
+    /// # Example
+    /// ```
+    /// use bloom_filter_rs::PrecisionBloom;
+    ///
+    /// let mut filter = PrecisionBloom::with_capacity(10, 0.01);
+    /// filter.insert(&"admin");
+    ///
+    /// let encoded = filter.to_base64();
+    /// let restored = PrecisionBloom::from_base64(&encoded).unwrap();
+    /// assert!(restored.contains(&"admin"));
+    /// ```
+    #[cfg(feature = "base64")]
+    pub fn to_base64(&self) -> String {
+        use base64::Engine;
+
+        base64::engine::general_purpose::STANDARD.encode(self.to_bytes())
+    }
+
+    /// Decode a filter produced by [`to_base64`](Self::to_base64).
+    ///
+    /// # Errors
+    /// Returns `BloomError::InvalidFormat` if the input is not valid base64 or
+    /// does not decode to a valid filter.
+    #[cfg(feature = "base64")]
+    pub fn from_base64(encoded: &str) -> Result<Self, BloomError> {
+        use base64::Engine;
+
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(encoded.trim())
+            .map_err(|err| BloomError::InvalidFormat(format!("invalid base64: {}", err)))?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+/// Filters are equal if they have the same parameters, hash configuration
+/// and bits.
+///
+/// Tracked insert and query counts, the query history and the overfill
+/// behavior are bookkeeping and are not compared.
+impl PartialEq for PrecisionBloom {
+    fn eq(&self, other: &Self) -> bool {
+        self.params == other.params
+            && self.hash_strategy == other.hash_strategy
+            && self.bits == other.bits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "rand")]
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_new_filter() {
+        let filter = PrecisionBloom::with_capacity(1000, 0.01);
+        assert_eq!(filter.len(), 0);
+        assert!(filter.is_empty());
+        assert_eq!(filter.capacity(), 1000);
+        assert!(filter.num_bits() > 9000 && filter.num_bits() < 10000);
+        assert!(filter.num_hashes() >= 6 && filter.num_hashes() <= 8);
+    }
+
+    #[test]
+    fn test_insert_and_contains() {
+        let mut filter = PrecisionBloom::with_capacity(100, 0.01);
+
+        // Insert some items
+        filter.insert(&"hello");
+        filter.insert(&"world");
+        filter.insert(&42);
+
+        // Check they are present
+        assert!(filter.contains(&"hello"));
+        assert!(filter.contains(&"world"));
+        assert!(filter.contains(&42));
+
+        // Check other items are not present
+        assert!(!filter.contains(&"foo"));
+        assert!(!filter.contains(&"bar"));
+        assert!(!filter.contains(&99));
+    }
+
+    #[test]
+    fn test_insert_returns_correct_value() {
+        let mut filter = PrecisionBloom::with_capacity(100, 0.01);
+
+        // First insert should return true (was absent)
+        assert!(filter.insert(&"hello"));
+
+        // Second insert might return false (might already be set)
+        // This depends on hash collisions, so we just check it doesn't panic
+        let _ = filter.insert(&"hello");
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut filter = PrecisionBloom::with_capacity(100, 0.01);
+
+        filter.insert(&"hello");
+        filter.insert(&"world");
+        assert!(filter.contains(&"hello"));
+        assert!(!filter.is_empty());
+
+        filter.clear();
+        assert!(!filter.contains(&"hello"));
+        assert!(!filter.contains(&"world"));
+        assert!(filter.is_empty());
+        assert_eq!(filter.len(), 0);
+    }
+
+    #[test]
+    fn test_len() {
+        let mut filter = PrecisionBloom::with_capacity(100, 0.01);
+
+        assert_eq!(filter.len(), 0);
+
+        filter.insert(&1);
+        assert_eq!(filter.len(), 1);
+
+        filter.insert(&2);
+        assert_eq!(filter.len(), 2);
+
+        filter.insert(&3);
+        assert_eq!(filter.len(), 3);
+    }
+
+    #[test]
+    fn test_saturation_increases() {
+        let mut filter = PrecisionBloom::with_capacity(100, 0.01);
+
+        let initial_saturation = filter.saturation();
+        assert_eq!(initial_saturation, 0.0);
+
+        // Insert items and check saturation increases
+        for i in 0..50 {
+            filter.insert(&i);
+        }
+
+        let mid_saturation = filter.saturation();
+        assert!(mid_saturation > 0.0);
+
+        for i in 50..100 {
+            filter.insert(&i);
+        }
+
+        let final_saturation = filter.saturation();
+        assert!(final_saturation > mid_saturation);
+    }
+
+    #[test]
+    fn test_no_false_negatives() {
+        let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+
+        let items: Vec<i32> = (0..1000).collect();
+
+        // Insert all items
+        for &item in &items {
+            filter.insert(&item);
+        }
+
+        // Check all items are present (no false negatives)
+        for &item in &items {
+            assert!(
+                filter.contains(&item),
+                "False negative detected for item {}",
+                item
+            );
+        }
+    }
+
+    #[test]
+    fn test_false_positive_rate() {
+        let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+
+        // Insert 1000 items
+        for i in 0..1000 {
+            filter.insert(&i);
+        }
+
+        // Test 10000 items that were NOT inserted
+        let mut false_positives = 0;
+        let test_count = 10000;
+
+        for i in 1000..(1000 + test_count) {
+            if filter.contains(&i) {
+                false_positives += 1;
+            }
+        }
+
+        let actual_fpr = false_positives as f64 / test_count as f64;
+
+        // Actual FPR should be close to target (within 3x for statistical variation)
+        assert!(
+            actual_fpr < 0.03,
+            "False positive rate too high: {:.4}",
+            actual_fpr
+        );
+    }
+
+    #[test]
+    fn test_is_overfilled() {
+        let mut filter = PrecisionBloom::with_capacity(10, 0.01);
+
+        assert!(!filter.is_overfilled());
+
+        for i in 0..10 {
+            filter.insert(&i);
+        }
+        assert!(!filter.is_overfilled());
+
+        filter.insert(&11);
+        assert!(filter.is_overfilled());
+    }
+
+    #[test]
+    fn test_different_types() {
+        let mut filter = PrecisionBloom::with_capacity(100, 0.01);
+
+        // Test with different types
+        filter.insert(&"string");
+        filter.insert(&42);
+        filter.insert(&(1, 2, 3));
+        filter.insert(&vec![1, 2, 3]);
+
+        assert!(filter.contains(&"string"));
+        assert!(filter.contains(&42));
+        assert!(filter.contains(&(1, 2, 3)));
+        assert!(filter.contains(&vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn test_status_summary() {
+        let mut filter = PrecisionBloom::with_capacity(100, 0.01);
+
+        for i in 0..50 {
+            filter.insert(&i);
+        }
+
+        let status = filter.status();
+        assert!(status.contains("50/100"));
+    }
+
+    #[test]
+    fn test_overfill_allow() {
+        let mut filter = PrecisionBloom::with_capacity(10, 0.01);
+        assert_eq!(filter.overfill_behavior(), OverfillBehavior::Allow);
+
+        for i in 0..20 {
+            assert!(filter.try_insert(&i).is_ok());
+        }
+        filter.insert(&20);
+        assert_eq!(filter.len(), 21);
+        assert!(filter.is_overfilled());
+    }
+
+    #[test]
+    fn test_overfill_reject() {
+        let mut filter = PrecisionBloom::with_capacity(10, 0.01)
+            .with_overfill_behavior(OverfillBehavior::Reject);
+
+        for i in 0..10 {
+            assert!(filter.try_insert(&i).is_ok());
+        }
+
+        let result = filter.try_insert(&10);
+        assert!(matches!(
+            result,
+            Err(BloomError::CapacityExceeded { capacity: 10 })
+        ));
+        assert_eq!(filter.len(), 10);
+        assert!(!filter.is_overfilled());
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity")]
+    fn test_overfill_reject_insert_panics() {
+        let mut filter =
+            PrecisionBloom::with_capacity(1, 0.01).with_overfill_behavior(OverfillBehavior::Reject);
+
+        filter.insert(&1);
+        filter.insert(&2);
+    }
+
+    #[test]
+    fn test_query_counting() {
+        let filter = PrecisionBloom::with_capacity(100, 0.01);
+        filter.contains(&1);
+        assert_eq!(filter.queries_performed(), 0);
+
+        let mut filter = filter.with_query_counting(true);
+        filter.insert(&1);
+        for i in 0..50 {
+            filter.contains(&i);
+        }
+        filter.contains_key(&7u64);
+        assert_eq!(filter.queries_performed(), 51);
+
+        filter.record_query();
+        assert_eq!(filter.queries_performed(), 52);
+
+        let restored = PrecisionBloom::from_bytes(&filter.to_bytes()).unwrap();
+        assert_eq!(restored.queries_performed(), 52);
+
+        filter.clear();
+        assert_eq!(filter.queries_performed(), 0);
+    }
+
+    #[test]
+    fn test_memory_usage() {
+        let filter = PrecisionBloom::new(BloomParameters::from_bit_count(100, 10));
+        assert_eq!(filter.memory_usage(), 16);
+    }
+
+    #[test]
+    fn test_memory_savings_vs_hashset() {
+        let mut filter = PrecisionBloom::with_capacity(1_000_000, 0.01);
+        assert_eq!(filter.memory_savings_vs_hashset(16), 0.0);
+
+        // Only the count matters, so skip the million inserts
+        filter.tracker.set_counts(1_000_000, 0);
+        let savings = filter.memory_savings_vs_hashset(16);
+        assert!(savings > 20.0, "got {}", savings);
+    }
+
+    #[test]
+    fn test_is_order_independent() {
+        let items: Vec<String> = (0..1000).map(|i| format!("item-{}", i)).collect();
+        assert!(PrecisionBloom::is_order_independent(&items, 0.01));
+
+        // Same parameters and count but different contents compare unequal,
+        // so the check is not vacuous
+        let shifted: Vec<String> = (1..1001).map(|i| format!("item-{}", i)).collect();
+        assert_ne!(
+            PrecisionBloom::from_set(&items, 0.01),
+            PrecisionBloom::from_set(&shifted, 0.01)
+        );
+    }
+
+    #[test]
+    fn test_equality_ignores_bookkeeping() {
+        let mut a = PrecisionBloom::with_capacity(100, 0.01);
+        let mut b = PrecisionBloom::with_capacity(100, 0.01);
+        a.insert(&"x");
+        b.insert(&"x");
+        b.insert(&"x");
+        for _ in 0..10 {
+            b.contains(&"y");
+        }
+        assert_ne!(a.len(), b.len());
+        assert_eq!(a, b);
+
+        let reseeded = PrecisionBloom::empty_like(&a).with_hash_backend(HashBackend::Seahash);
+        assert_ne!(PrecisionBloom::empty_like(&a), reseeded);
+    }
+
+    #[test]
+    fn test_with_fixed_keys() {
+        let params = BloomParameters::from_item_count(1000, 0.01);
+        let build = |ahash_keys| {
+            let mut filter = PrecisionBloom::with_fixed_keys(params, ahash_keys, [9, 8, 7, 6]);
+            for i in 0..500 {
+                filter.insert(&i);
+                filter.insert_key(&format!("key-{}", i));
+            }
+            filter
+        };
+
+        let a = build([1, 2, 3, 4]);
+        assert_eq!(a, build([1, 2, 3, 4]));
+        assert_ne!(a, build([4, 3, 2, 1]));
+        assert!((0..500).all(|i| a.contains(&i)));
+
+        // The keys are stored, so the restored filter hashes the same way
+        let restored = PrecisionBloom::from_bytes(&a.to_bytes()).unwrap();
+        assert_eq!(restored.hash_strategy.backend(), a.hash_strategy.backend());
+        assert_eq!(restored, a);
+    }
+
+    #[test]
+    fn test_distinguishing_positions() {
+        let filter = PrecisionBloom::with_capacity(1000, 0.01);
+
+        assert!(filter
+            .distinguishing_positions(&"apple", &"apple")
+            .is_empty());
+
+        let positions = filter.distinguishing_positions(&"apple", &"banana");
+        assert!(!positions.is_empty());
+        assert!(positions.windows(2).all(|w| w[0] < w[1]));
+        assert!(positions.iter().all(|&p| p < filter.num_bits()));
+        assert_eq!(
+            positions,
+            filter.distinguishing_positions(&"banana", &"apple")
+        );
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_insert_with_probability() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+        let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+
+        filter.insert_with_probability(&"never", 0.0, &mut rng);
+        assert_eq!(filter.bits.count_ones(), 0);
+
+        let mut inserts = 0;
+        while !filter.contains(&"frequent") {
+            filter.insert_with_probability(&"frequent", 0.5, &mut rng);
+            inserts += 1;
+            assert!(inserts < 100, "item never became present");
+        }
+        assert_eq!(filter.len(), inserts + 1);
+    }
+
+    #[test]
+    fn test_longest_zero_run() {
+        let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+        assert_eq!(filter.longest_zero_run(), (0, filter.num_bits()));
+
+        filter.insert(&"first");
+        let (_, after_one) = filter.longest_zero_run();
+        assert!(after_one < filter.num_bits());
+        assert!(after_one >= filter.num_bits() / (filter.num_hashes() + 1));
+
+        for i in 0..1000 {
+            filter.insert(&i);
+        }
+        let (start, len) = filter.longest_zero_run();
+        assert!(len < after_one);
+        assert!((start..start + len).all(|i| !filter.bits.get(i)));
+
+        let mut bits = BitArray::new(130);
+        for i in [0, 3, 70, 129] {
+            bits.set(i);
+        }
+        assert_eq!(bits.longest_zero_run(), (4, 66));
+    }
+
+    #[test]
+    fn test_from_prehashed() {
+        let params = BloomParameters::from_item_count(1000, 0.01);
+        let strategy = HashStrategy::new(params.num_hashes, params.num_bits);
+        let pairs: Vec<(u64, u64)> = (0..500).map(|i| strategy.base_hashes(&i)).collect();
+
+        let filter = PrecisionBloom::from_prehashed(params, pairs);
+        assert_eq!(filter.len(), 500);
+        for i in 0..500 {
+            assert!(filter.contains(&i));
+        }
+
+        let mut direct = PrecisionBloom::new(params);
+        for i in 0..500 {
+            direct.insert(&i);
+        }
+        assert_eq!(filter.bits, direct.bits);
+    }
+
+    #[test]
+    fn test_provenance() {
+        let filter = PrecisionBloom::with_capacity(100, 0.01);
+        let provenance = filter.provenance();
+        assert_eq!(provenance.backend, "ahash+seahash");
+        assert_eq!(provenance.scheme, "kirsch-mitzenmacher");
+        assert_eq!(provenance.format_version, FORMAT_VERSION);
+        assert_eq!(provenance.crate_version, env!("CARGO_PKG_VERSION"));
+
+        let seahash = filter.with_hash_backend(HashBackend::Seahash);
+        assert_eq!(seahash.provenance().backend, "seahash+seahash");
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_positive_confidence() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+        let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+        filter.insert(&"present");
+
+        let absent = (0..).find(|i| !filter.contains(i)).unwrap();
+        assert_eq!(filter.positive_confidence(&absent), None);
+
+        let sparse = filter.positive_confidence(&"present").unwrap();
+        assert!(sparse > 0.999_999, "got {}", sparse);
+
+        filter.fill_to_saturation(0.8, &mut rng);
+        let full = filter.positive_confidence(&"present").unwrap();
+        assert!(full < sparse);
+        assert!(full < 0.9, "got {}", full);
+    }
+
+    #[test]
+    fn test_union_with_keeps_popcount() {
+        let mut a = PrecisionBloom::with_capacity(1000, 0.01);
+        let mut b = PrecisionBloom::with_capacity(1000, 0.01);
+        for i in 0..300 {
+            a.insert(&i);
+            b.insert(&(i + 200));
+        }
+
+        a.union_with(&b).unwrap();
+        assert_eq!(a.len(), 600);
+        assert!((0..500).all(|i| a.contains(&i)));
+
+        let fresh: usize = a
+            .bits
+            .as_words()
+            .iter()
+            .map(|w| w.count_ones() as usize)
+            .sum();
+        assert_eq!(a.bits.count_ones(), fresh);
+
+        let smaller = PrecisionBloom::with_capacity(500, 0.01);
+        assert!(matches!(
+            a.union_with(&smaller),
+            Err(BloomError::IncompatibleFilters)
+        ));
+    }
+
+    #[test]
+    fn test_bit_array_ops_keep_popcount() {
+        let recount = |bits: &BitArray| -> usize {
+            bits.as_words()
+                .iter()
+                .map(|w| w.count_ones() as usize)
+                .sum()
+        };
+
+        let mut a = BitArray::new(200);
+        let mut b = BitArray::new(200);
+        for i in (0..200).step_by(3) {
+            a.set(i);
+        }
+        for i in (0..200).step_by(5) {
+            b.set(i);
+        }
+        a.set(0);
+        assert_eq!(a.count_ones(), 67);
+
+        let mut or = a.clone();
+        or.or_with(&b);
+        assert_eq!(or.count_ones(), recount(&or));
+        assert_eq!(or.count_ones(), 67 + 40 - 14);
+
+        let mut and = a.clone();
+        and.and_with(&b);
+        assert_eq!(and.count_ones(), 14);
+
+        let mut xor = a.clone();
+        xor.xor_with(&b);
+        assert_eq!(xor.count_ones(), recount(&xor));
+
+        xor.unset(1);
+        xor.unset(3);
+        assert_eq!(xor.count_ones(), recount(&xor));
+        xor.clear();
+        assert_eq!(xor.count_ones(), 0);
+
+        let words = or.as_words().to_vec();
+        assert_eq!(
+            BitArray::from_words(words, 200).count_ones(),
+            or.count_ones()
+        );
+    }
+
+    #[test]
+    fn test_current_optimal_hashes() {
+        let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+        assert_eq!(filter.current_optimal_hashes(), filter.num_hashes());
+
+        for i in 0..1000 {
+            filter.insert(&i);
+        }
+        assert!(
+            filter
+                .current_optimal_hashes()
+                .abs_diff(filter.num_hashes())
+                <= 1
+        );
+
+        for i in 1000..4000 {
+            filter.insert(&i);
+        }
+        assert!(filter.current_optimal_hashes() < filter.num_hashes());
+        assert_eq!(filter.current_optimal_hashes(), 2);
+    }
+
+    #[test]
+    fn test_forecast_capacity_exhaustion() {
+        let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+        for i in 0..500 {
+            filter.insert(&i);
+        }
+
+        let slow = filter.forecast_capacity_exhaustion(10.0, 0.02).unwrap();
+        let fast = filter.forecast_capacity_exhaustion(100.0, 0.02).unwrap();
+        assert!(fast < slow);
+        let headroom = filter.parameters().max_items_for_fpr(0.02) - 500;
+        assert_eq!(fast, headroom as f64 / 100.0);
+
+        assert_eq!(filter.forecast_capacity_exhaustion(0.0, 0.02), None);
+        assert_eq!(filter.forecast_capacity_exhaustion(10.0, 1.0), None);
+
+        for i in 500..3000 {
+            filter.insert(&i);
+        }
+        assert_eq!(filter.forecast_capacity_exhaustion(10.0, 0.02), Some(0.0));
+    }
+
+    #[test]
+    fn test_is_union_compatible() {
+        let a = PrecisionBloom::with_capacity(1000, 0.01);
+        let b = PrecisionBloom::with_capacity(1000, 0.01);
+        let smaller = PrecisionBloom::with_capacity(500, 0.01);
+        assert!(a.is_union_compatible(&b));
+        assert!(!a.is_union_compatible(&smaller));
+
+        let mut other_backend = b.clone();
+        other_backend.hash_strategy =
+            HashStrategy::with_backend(b.num_hashes(), b.num_bits(), HashBackend::Seahash);
+        assert!(!a.is_union_compatible(&other_backend));
+    }
+
+    #[test]
+    fn test_from_parts_estimate_then_clear() {
+        let mut original = PrecisionBloom::with_capacity(1000, 0.01);
+        for i in 0..500 {
+            original.insert(&i);
+        }
+
+        let mut filter =
+            PrecisionBloom::from_parts(*original.parameters(), (*original.bits).clone()).unwrap();
+        assert_eq!(filter.len(), 0);
+
+        let estimate = filter.estimate_and_set_item_count();
+        assert!((475..525).contains(&estimate), "got {}", estimate);
+        assert_eq!(filter.len(), estimate);
+
+        filter.clear();
+        assert!(filter.is_empty());
+        assert!(!filter.contains(&0));
+
+        // Clearing an already empty filter leaves it consistent
+        filter.clear();
+        assert!(filter.is_empty());
+        assert_eq!(filter.saturation(), 0.0);
+
+        filter.insert(&"after");
+        assert_eq!(filter.len(), 1);
+        assert!(filter.contains(&"after"));
+    }
+
+    #[test]
+    fn test_from_parts_rejects_mismatched_bits() {
+        let params = BloomParameters::from_item_count(100, 0.01);
+        let result = PrecisionBloom::from_parts(params, BitArray::new(params.num_bits + 1));
+        assert!(matches!(result, Err(BloomError::InvalidFormat(_))));
+    }
+
+    #[test]
+    fn test_fpr_after_inserting() {
+        let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+        for i in 0..500 {
+            filter.insert(&i);
+        }
+
+        assert_eq!(
+            filter.fpr_after_inserting(0),
+            filter.actual_false_positive_rate()
+        );
+        assert!(filter.fpr_after_inserting(100) > filter.fpr_after_inserting(0));
+        assert!(filter.fpr_after_inserting(1000) > filter.fpr_after_inserting(100));
+    }
+
+    #[test]
+    fn test_is_near_fpr_cliff() {
+        let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+        assert!(!filter.is_near_fpr_cliff(100, 0.02));
+
+        for i in 0..950 {
+            filter.insert(&i);
+        }
+        assert!(filter.is_near_fpr_cliff(300, 0.02));
+        assert!(!filter.is_near_fpr_cliff(0, 0.02));
+    }
+
+    #[test]
+    fn test_expected_queries_per_false_positive() {
+        let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+        assert!(filter.expected_queries_per_false_positive().is_infinite());
+
+        for i in 0..1000 {
+            filter.insert(&i);
+        }
+        let queries = filter.expected_queries_per_false_positive();
+        assert!((90.0..110.0).contains(&queries), "got {}", queries);
+    }
+
+    #[test]
+    fn test_key_hasher_matches_concatenated_key() {
+        let key: Vec<u8> = (0..100u8).collect();
+
+        for backend in [HashBackend::AhashSeahash, HashBackend::Seahash] {
+            let mut filter = PrecisionBloom::with_capacity(100, 0.01);
+            filter.hash_strategy =
+                HashStrategy::with_backend(filter.num_hashes(), filter.num_bits(), backend);
+
+            // Split off-block so the second chunk straddles a block boundary
+            let mut hasher = filter.key_hasher();
+            hasher.update(&key[..45]);
+            hasher.update(&key[45..]);
+            assert!(hasher.finish_insert(&mut filter));
+
+            assert!(filter.contains_key(key.as_slice()));
+            assert!(!filter.insert_key(key.as_slice()));
+
+            let mut hasher = filter.key_hasher();
+            for byte in &key {
+                hasher.update(std::slice::from_ref(byte));
+            }
+            assert!(hasher.finish_contains(&filter));
+        }
+    }
+
+    #[test]
+    fn test_with_same_hashers_at_fpr() {
+        let mut filter = PrecisionBloom::with_capacity(1000, 0.05);
+        filter.hash_strategy = HashStrategy::with_backend(
+            filter.num_hashes(),
+            filter.num_bits(),
+            HashBackend::Seahash,
+        );
+
+        let items: Vec<u32> = (0..1000).collect();
+        for item in &items {
+            filter.insert(item);
+        }
+
+        let tightened = filter.with_same_hashers_at_fpr(&items, 0.001);
+        assert_eq!(tightened.capacity(), 1000);
+        assert_eq!(tightened.hash_strategy.backend(), HashBackend::Seahash);
+        assert!(tightened.num_bits() > filter.num_bits());
+        assert!(tightened.actual_false_positive_rate() < 0.0011);
+        assert!(items.iter().all(|item| tightened.contains(item)));
     }
 
-    /// Get the number of bits in the filter.
-    pub fn num_bits(&self) -> usize {
-        self.params.num_bits
-    }
+    #[test]
+    fn test_compact_from_items() {
+        let mut filter = PrecisionBloom::with_capacity(10_000, 0.01);
+        for i in 0..10_000 {
+            filter.insert(&i);
+        }
+        filter.clear();
 
-    /// Get the number of hash functions used.
-    pub fn num_hashes(&self) -> usize {
-        self.params.num_hashes
-    }
+        let items: Vec<u32> = (0..500).collect();
+        for item in &items {
+            filter.insert(item);
+        }
 
-    /// Get the theoretical false positive rate.
-    pub fn false_positive_rate(&self) -> f64 {
-        self.tracker.theoretical_fpr()
+        let compact = filter.compact_from_items(&items, 0.01);
+        assert!(compact.num_bits() < filter.num_bits() / 10);
+        assert_eq!(compact.len(), 500);
+        assert!(compact.actual_false_positive_rate() < 0.011);
+        assert!(items.iter().all(|item| compact.contains(item)));
     }
 
-    /// Get the actual false positive rate based on current fill level.
-    pub fn actual_false_positive_rate(&self) -> f64 {
-        self.tracker.actual_fpr()
-    }
+    #[test]
+    fn test_distinct_positions() {
+        let filter = PrecisionBloom::with_capacity(1000, 0.01);
 
-    /// Get the saturation level of the filter (proportion of bits set).
-    ///
-    /// Returns a value between 0.0 (empty) and 1.0 (completely full).
-    pub fn saturation(&self) -> f64 {
-        self.bits.saturation()
-    }
+        let full = (0..1000)
+            .filter(|i| filter.distinct_positions(i) == filter.num_hashes())
+            .count();
+        assert!(full > 990, "only {} items used all positions", full);
 
-    /// Check if the filter has exceeded its expected capacity.
-    pub fn is_overfilled(&self) -> bool {
-        self.tracker.is_overfilled()
+        for i in 0..1000 {
+            assert!(filter.distinct_positions(&i) <= filter.num_hashes());
+        }
     }
 
-    /// Get a status summary of the filter.
-    pub fn status(&self) -> String {
-        self.tracker.status_summary()
-    }
+    #[test]
+    fn test_effective_num_hashes() {
+        let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+        let sample: Vec<u32> = (0..1000).collect();
+        for item in &sample {
+            filter.insert(item);
+        }
 
-    /// Get the parameters of this filter.
-    pub fn parameters(&self) -> &BloomParameters {
-        &self.params
+        let k = filter.num_hashes() as f64;
+        let effective = filter.effective_num_hashes(&sample);
+        assert!(effective <= k && effective > k - 0.05, "got {}", effective);
+
+        let fpr = filter.effective_fpr(&sample);
+        let theoretical = filter.actual_false_positive_rate();
+        assert!((fpr - theoretical).abs() < theoretical * 0.05);
+
+        let empty: [u32; 0] = [];
+        assert_eq!(filter.effective_num_hashes(&empty), k);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_insert_preview() {
+        let mut filter = PrecisionBloom::with_capacity(100, 0.01);
+        for i in 0..50 {
+            filter.insert(&i);
+        }
+
+        let preview = filter.insert_preview(&"new item");
+        assert!(!preview.is_empty());
+
+        let before = filter.bits.clone();
+        filter.insert(&"new item");
+
+        let flipped: Vec<usize> = (0..filter.num_bits())
+            .filter(|&i| filter.bits.get(i) && !before.get(i))
+            .collect();
+        assert_eq!(preview, flipped);
+
+        // Once inserted there is nothing left to set
+        assert!(filter.insert_preview(&"new item").is_empty());
+    }
 
     #[test]
-    fn test_new_filter() {
-        let filter = PrecisionBloom::with_capacity(1000, 0.01);
-        assert_eq!(filter.len(), 0);
-        assert!(filter.is_empty());
-        assert_eq!(filter.capacity(), 1000);
-        assert!(filter.num_bits() > 9000 && filter.num_bits() < 10000);
-        assert!(filter.num_hashes() >= 6 && filter.num_hashes() <= 8);
+    fn test_posterior_present_probability() {
+        let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+        for i in 0..1000 {
+            filter.insert(&i);
+        }
+        let fpr = filter.actual_false_positive_rate();
+
+        let even = filter.posterior_present_probability(0.5);
+        assert!((even - 1.0 / (1.0 + fpr)).abs() < 1e-12);
+        assert!(even > 0.98);
+
+        // A prior far below the FPR leaves a positive mostly meaningless
+        let rare = filter.posterior_present_probability(1e-4);
+        assert!(rare < 0.02, "got {}", rare);
+
+        assert_eq!(filter.posterior_present_probability(0.0), 0.0);
+        assert_eq!(filter.posterior_present_probability(1.0), 1.0);
     }
 
     #[test]
-    fn test_insert_and_contains() {
+    fn test_contains_with_confidence() {
         let mut filter = PrecisionBloom::with_capacity(100, 0.01);
+        for i in 0..100 {
+            filter.insert(&i);
+        }
 
-        // Insert some items
-        filter.insert(&"hello");
-        filter.insert(&"world");
-        filter.insert(&42);
+        let (present, confidence) = filter.contains_with_confidence(&50);
+        assert!(present);
+        assert!(confidence < 1.0);
+        assert!((confidence - (1.0 - filter.actual_false_positive_rate())).abs() < 1e-12);
 
-        // Check they are present
-        assert!(filter.contains(&"hello"));
-        assert!(filter.contains(&"world"));
-        assert!(filter.contains(&42));
+        assert_eq!(filter.contains_with_confidence(&"absent"), (false, 1.0));
+    }
 
-        // Check other items are not present
-        assert!(!filter.contains(&"foo"));
-        assert!(!filter.contains(&"bar"));
-        assert!(!filter.contains(&99));
+    #[test]
+    fn test_debug_dump() {
+        let build = |items: std::ops::Range<u32>| {
+            let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+            for i in items {
+                filter.insert(&i);
+            }
+            filter
+        };
+
+        let dump = build(0..100).debug_dump();
+        assert_eq!(dump, build(0..100).debug_dump());
+        assert_ne!(dump, build(0..101).debug_dump());
+
+        assert!(dump.starts_with("num_bits=9586\nnum_hashes=7\n"));
+        assert!(dump.contains("items_inserted=100\n"));
+        assert!(dump.contains("words=150\n"));
     }
 
     #[test]
-    fn test_insert_returns_correct_value() {
-        let mut filter = PrecisionBloom::with_capacity(100, 0.01);
+    fn test_fit_for_sets() {
+        let present: Vec<u32> = (0..1000).collect();
+        let absent: Vec<u32> = (1000..6000).collect();
 
-        // First insert should return true (was absent)
-        assert!(filter.insert(&"hello"));
+        let filter = PrecisionBloom::fit_for_sets(&present, &absent, 0.1);
 
-        // Second insert might return false (might already be set)
-        // This depends on hash collisions, so we just check it doesn't panic
-        let _ = filter.insert(&"hello");
+        assert!(present.iter().all(|item| filter.contains(item)));
+        assert!(absent.iter().all(|item| !filter.contains(item)));
+        assert!(filter.false_positive_rate() < 0.1);
     }
 
     #[test]
-    fn test_clear() {
-        let mut filter = PrecisionBloom::with_capacity(100, 0.01);
+    fn test_fpr_history() {
+        let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
 
-        filter.insert(&"hello");
-        filter.insert(&"world");
-        assert!(filter.contains(&"hello"));
-        assert!(!filter.is_empty());
+        // Disabled by default
+        filter.record_fpr_sample();
+        assert!(filter.fpr_history().is_empty());
 
-        filter.clear();
-        assert!(!filter.contains(&"hello"));
-        assert!(!filter.contains(&"world"));
-        assert!(filter.is_empty());
-        assert_eq!(filter.len(), 0);
+        filter.enable_fpr_history(5);
+        for batch in 0..12 {
+            for i in 0..100 {
+                filter.insert(&(batch * 100 + i));
+            }
+            filter.record_fpr_sample();
+        }
+
+        // Only the last 5 samples are kept, and FPR rises as the filter fills
+        let history = filter.fpr_history();
+        assert_eq!(history.len(), 5);
+        assert_eq!(history[0].items_inserted, 800);
+        assert_eq!(history[4].items_inserted, 1200);
+        for pair in history.windows(2) {
+            assert!(pair[1].theoretical_fpr > pair[0].theoretical_fpr);
+        }
     }
 
     #[test]
-    fn test_len() {
-        let mut filter = PrecisionBloom::with_capacity(100, 0.01);
+    fn test_recent_novelty_rate() {
+        let mut filter = PrecisionBloom::with_capacity(10_000, 0.01);
+        assert_eq!(filter.recent_novelty_rate(), 1.0);
 
-        assert_eq!(filter.len(), 0);
+        for i in 0..2000 {
+            assert!(filter.insert_if_absent(&i));
+        }
+        assert_eq!(filter.recent_novelty_rate(), 1.0);
+        assert_eq!(filter.len(), 2000);
 
-        filter.insert(&1);
-        assert_eq!(filter.len(), 1);
+        for i in 0..2000 {
+            assert!(!filter.insert_if_absent(&i));
+        }
+        assert_eq!(filter.recent_novelty_rate(), 0.0);
+        assert_eq!(filter.len(), 2000);
+    }
 
-        filter.insert(&2);
-        assert_eq!(filter.len(), 2);
+    #[test]
+    fn test_estimated_cardinality() {
+        let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+        assert_eq!(filter.estimated_cardinality(), 0.0);
 
-        filter.insert(&3);
-        assert_eq!(filter.len(), 3);
+        for i in 0..1000 {
+            filter.insert(&i);
+        }
+        let estimate = filter.estimated_cardinality();
+        assert!((estimate - 1000.0).abs() < 50.0, "estimate {}", estimate);
+
+        // Duplicates do not move the estimate
+        for i in 0..1000 {
+            filter.insert(&i);
+        }
+        assert_eq!(filter.estimated_cardinality(), estimate);
     }
 
     #[test]
-    fn test_saturation_increases() {
-        let mut filter = PrecisionBloom::with_capacity(100, 0.01);
+    fn test_tracked_vs_estimated() {
+        let mut duplicates = PrecisionBloom::with_capacity(1000, 0.01);
+        for _ in 0..1000 {
+            duplicates.insert(&"same");
+        }
+        let (tracked, estimated) = duplicates.tracked_vs_estimated();
+        assert_eq!(tracked, 1000);
+        assert!(estimated < 2.0, "got {}", estimated);
 
-        let initial_saturation = filter.saturation();
-        assert_eq!(initial_saturation, 0.0);
+        let mut unique = PrecisionBloom::with_capacity(1000, 0.01);
+        for i in 0..1000 {
+            unique.insert(&i);
+        }
+        let (tracked, estimated) = unique.tracked_vs_estimated();
+        assert_eq!(tracked, 1000);
+        assert!((estimated - 1000.0).abs() < 50.0, "got {}", estimated);
+    }
 
-        // Insert items and check saturation increases
-        for i in 0..50 {
+    #[test]
+    fn test_suggested_rebuild_params() {
+        let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+        for i in 0..3000 {
             filter.insert(&i);
         }
+        assert!(filter.is_overfilled());
 
-        let mid_saturation = filter.saturation();
-        assert!(mid_saturation > 0.0);
+        let estimate = filter.estimated_cardinality();
+        let params = filter.suggested_rebuild_params(0.5);
+        let ratio = params.expected_items as f64 / estimate;
 
-        for i in 50..100 {
+        assert!((ratio - 1.5).abs() < 0.01, "ratio {}", ratio);
+        assert_eq!(params.false_positive_rate, 0.01);
+        assert!(params.num_bits > filter.num_bits());
+    }
+
+    #[test]
+    fn test_snapshot_copy_on_write() {
+        let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+        for i in 0..100 {
             filter.insert(&i);
         }
 
-        let final_saturation = filter.saturation();
-        assert!(final_saturation > mid_saturation);
+        let snapshot = filter.snapshot();
+        assert!(Arc::ptr_eq(&filter.bits, &snapshot.bits));
+
+        filter.insert(&"new");
+        assert!(!Arc::ptr_eq(&filter.bits, &snapshot.bits));
+        assert!(filter.contains(&"new"));
+        assert!(!snapshot.contains(&"new"));
+        assert_eq!(snapshot.len(), 100);
+
+        // Clearing a shared filter leaves the snapshot intact
+        let snapshot = filter.snapshot();
+        filter.clear();
+        assert!(filter.is_empty());
+        assert!((0..100).all(|i| snapshot.contains(&i)));
     }
 
     #[test]
-    fn test_no_false_negatives() {
+    fn test_insert_until_fpr() {
         let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+        let (inserted, stopped_early) = filter.insert_until_fpr(0..10_000, 0.02);
 
-        let items: Vec<i32> = (0..1000).collect();
+        assert!(stopped_early);
+        assert_eq!(inserted, filter.len());
+        assert!(filter.actual_false_positive_rate() <= 0.02);
+        assert!(filter.fpr_after_inserting(1) > 0.02);
 
-        // Insert all items
-        for &item in &items {
-            filter.insert(&item);
+        // A short stream is consumed entirely
+        let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+        assert_eq!(filter.insert_until_fpr(0..100, 0.02), (100, false));
+    }
+
+    #[test]
+    fn test_probably_contains_all() {
+        let items: Vec<u32> = (0..1000).collect();
+        let filter = PrecisionBloom::from_set(&items, 0.01);
+        assert_eq!(filter.probably_contains_all(&items), Ok(()));
+
+        let absent = (1000..).find(|i| !filter.contains(i)).unwrap();
+        let mut batch = items.clone();
+        batch.insert(637, absent);
+        assert_eq!(filter.probably_contains_all(&batch), Err(637));
+
+        let empty: [u32; 0] = [];
+        assert_eq!(filter.probably_contains_all(&empty), Ok(()));
+    }
+
+    #[test]
+    fn test_dedup_iter() {
+        let mut filter = PrecisionBloom::with_capacity(100, 0.01);
+        let unique: Vec<i32> = filter.dedup_iter([1, 1, 2, 2, 3]).collect();
+        assert_eq!(unique, vec![1, 2, 3]);
+        assert_eq!(filter.len(), 3);
+
+        // State carries over between calls
+        let unique: Vec<i32> = filter.dedup_iter([3, 4]).collect();
+        assert_eq!(unique, vec![4]);
+    }
+
+    #[test]
+    fn test_single_hash_fast_path() {
+        let params = BloomParameters::from_bit_count(10_000, 20_000);
+        assert_eq!(params.num_hashes, 1);
+
+        let mut filter = PrecisionBloom::new(params);
+        let strategy = HashStrategy::new(1, params.num_bits);
+        let mut reference = BitArray::new(params.num_bits);
+
+        for i in 0..2_000 {
+            filter.insert(&i);
+            for index in strategy.hash_indices(&i) {
+                reference.set(index);
+            }
         }
 
-        // Check all items are present (no false negatives)
-        for &item in &items {
-            assert!(
-                filter.contains(&item),
-                "False negative detected for item {}",
-                item
-            );
+        assert_eq!(*filter.bits, reference);
+        for i in 0..10_000 {
+            let expected = strategy
+                .hash_indices(&i)
+                .iter()
+                .all(|&index| reference.get(index));
+            assert_eq!(filter.contains(&i), expected);
         }
     }
 
     #[test]
-    fn test_false_positive_rate() {
+    fn test_audit_consistency() {
         let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+        for i in 0..1000 {
+            filter.insert(&i);
+        }
+        assert!(filter.audit_consistency(0.02).is_ok());
 
-        // Insert 1000 items
+        // Duplicate inserts leave saturation below the prediction
         for i in 0..1000 {
             filter.insert(&i);
         }
+        assert!(filter.audit_consistency(0.02).is_ok());
+
+        // All bits set while claiming only a handful of items
+        let words = vec![u64::MAX; filter.bits.as_words().len()];
+        let mut tampered = PrecisionBloom::from_parts(
+            *filter.parameters(),
+            BitArray::from_words(words, filter.num_bits()),
+        )
+        .unwrap();
+        tampered.tracker.set_counts(10, 0);
+        assert!(tampered.audit_consistency(0.02).is_err());
+    }
 
-        // Test 10000 items that were NOT inserted
-        let mut false_positives = 0;
-        let test_count = 10000;
+    #[test]
+    fn test_validate_against() {
+        let truth: HashSet<u32> = (0..500).collect();
 
-        for i in 1000..(1000 + test_count) {
-            if filter.contains(&i) {
-                false_positives += 1;
-            }
+        let mut filter = PrecisionBloom::with_capacity(500, 0.01);
+        for item in &truth {
+            filter.insert(item);
         }
 
-        let actual_fpr = false_positives as f64 / test_count as f64;
+        let report = filter.validate_against(&truth);
+        assert_eq!(report.items_checked, 500);
+        assert_eq!(report.false_negatives, 0);
+        assert!(report.is_valid());
 
-        // Actual FPR should be close to target (within 3x for statistical variation)
-        assert!(
-            actual_fpr < 0.03,
-            "False positive rate too high: {:.4}",
-            actual_fpr
-        );
+        // A filter missing items reports them
+        let partial = PrecisionBloom::with_capacity(500, 0.01);
+        let report = partial.validate_against(&truth);
+        assert_eq!(report.false_negatives, 500);
+        assert!(!report.is_valid());
     }
 
+    #[cfg(feature = "rand")]
     #[test]
-    fn test_is_overfilled() {
-        let mut filter = PrecisionBloom::with_capacity(10, 0.01);
+    fn test_fill_to_saturation() {
+        let mut filter = PrecisionBloom::with_capacity(10_000, 0.01);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
 
-        assert!(!filter.is_overfilled());
+        filter.fill_to_saturation(0.3, &mut rng);
+        assert!((filter.saturation() - 0.3).abs() < 1e-3);
+        assert_eq!(filter.len(), 0);
 
-        for i in 0..10 {
-            filter.insert(&i);
-        }
-        assert!(!filter.is_overfilled());
+        filter.fill_to_saturation(0.75, &mut rng);
+        assert!((filter.saturation() - 0.75).abs() < 1e-3);
 
-        filter.insert(&11);
-        assert!(filter.is_overfilled());
+        // Lower targets leave the filter untouched
+        filter.fill_to_saturation(0.5, &mut rng);
+        assert!((filter.saturation() - 0.75).abs() < 1e-3);
     }
 
     #[test]
-    fn test_different_types() {
+    fn test_insert_collision_probability() {
         let mut filter = PrecisionBloom::with_capacity(100, 0.01);
+        assert_eq!(filter.insert_collision_probability(), 0.0);
 
-        // Test with different types
-        filter.insert(&"string");
-        filter.insert(&42);
-        filter.insert(&(1, 2, 3));
-        filter.insert(&vec![1, 2, 3]);
+        for i in 0..100 {
+            filter.insert(&i);
+        }
+        let at_capacity = filter.insert_collision_probability();
+        assert!(at_capacity > 0.0 && at_capacity < 0.05);
 
-        assert!(filter.contains(&"string"));
-        assert!(filter.contains(&42));
-        assert!(filter.contains(&(1, 2, 3)));
-        assert!(filter.contains(&vec![1, 2, 3]));
+        for i in 100..2000 {
+            filter.insert(&i);
+        }
+        let saturated = filter.insert_collision_probability();
+        assert!(saturated > at_capacity);
+        assert!(saturated > 0.9);
     }
 
+    #[cfg(feature = "rand")]
     #[test]
-    fn test_status_summary() {
-        let mut filter = PrecisionBloom::with_capacity(100, 0.01);
+    fn test_expected_new_bits_per_insert() {
+        let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+        let k = filter.num_hashes() as f64;
+        assert_eq!(filter.expected_new_bits_per_insert(), k);
 
-        for i in 0..50 {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        filter.fill_to_saturation(0.99, &mut rng);
+        assert!(filter.expected_new_bits_per_insert() < 0.02 * k);
+    }
+
+    #[test]
+    fn test_bits_to_target_fpr() {
+        let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+        let empty_gap = filter.bits_to_target_fpr(0.01);
+        assert!(empty_gap > 0);
+
+        for i in 0..500 {
             filter.insert(&i);
         }
+        let half_gap = filter.bits_to_target_fpr(0.01);
+        assert!(half_gap > 0 && half_gap < empty_gap);
+        assert_eq!(
+            filter.bits_to_target_fpr(1.0),
+            filter.num_bits() as i64 - filter.bits.count_ones() as i64
+        );
 
-        let status = filter.status();
-        assert!(status.contains("50/100"));
+        for i in 500..3000 {
+            filter.insert(&i);
+        }
+        assert!(filter.bits_to_target_fpr(0.01) < 0);
+        assert_eq!(
+            filter.bits_to_target_fpr(0.0),
+            -(filter.bits.count_ones() as i64)
+        );
     }
 }
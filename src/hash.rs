@@ -3,7 +3,224 @@
 //! This implementation uses two independent hash functions (ahash and seahash)
 //! combined with Kirsch-Mitzenmacher double hashing to generate k hash values with good distribution.
 
-use std::hash::{Hash, Hasher};
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crate::key::BloomKey;
+
+/// Keys for the ahash hasher of [`HashBackend::AhashSeahash`].
+///
+/// `AHasher::default()` is keyed randomly per process, which would place the
+/// same item on different bits in every run and make persisted filters report
+/// false negatives once reloaded. Fixed keys keep placement deterministic.
+const AHASH_KEYS: [u64; 4] = [
+    0xb7e1_5162_8aed_2a6a,
+    0xbf71_5880_9cf4_f3c7,
+    0x62e7_160f_38b4_da56,
+    0xa784_d904_5190_cfef,
+];
+
+/// Seeds for the first seahash instance of [`HashBackend::Seahash`].
+const SEAHASH_SEEDS_1: [u64; 4] = [
+    0x243f_6a88_85a3_08d3,
+    0x1319_8a2e_0370_7344,
+    0xa409_3822_299f_31d0,
+    0x082e_fa98_ec4e_6c89,
+];
+
+/// Seeds for the second seahash instance of [`HashBackend::Seahash`].
+const SEAHASH_SEEDS_2: [u64; 4] = [
+    0x4528_21e6_38d0_1377,
+    0xbe54_66cf_34e9_0c6c,
+    0xc0ac_29b7_c97c_50dd,
+    0x3f84_d5b5_b547_0917,
+];
+
+/// Seeds for [`shard_of`], independent of both backends' seeds.
+const SHARD_SEEDS: [u64; 4] = [
+    0x9216_d5d9_8979_fb1b,
+    0xd131_0ba6_98df_b5ac,
+    0x2ffd_72db_d01a_dfb7,
+    0xb8e1_afed_6a26_7e96,
+];
+
+/// Values hashed in debug builds to check that the two base hashers differ.
+#[cfg(debug_assertions)]
+const INDEPENDENCE_PROBES: [u64; 4] = [0, 1, 0xdead_beef, u64::MAX];
+
+/// The pair of hash functions used to derive the two base hashes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HashBackend {
+    /// ahash as the primary hash and seahash as the secondary, both with
+    /// fixed keys built into the crate
+    #[default]
+    AhashSeahash,
+    /// seahash for both hashes, keyed with two independent seed sets
+    Seahash,
+    /// ahash and seahash keyed with caller-supplied keys
+    ///
+    /// Every backend places items deterministically; custom keys make
+    /// placement hard to predict for anyone who does not know them. The keys
+    /// are written in plain form by `PrecisionBloom::to_bytes`, so a restored
+    /// filter hashes the same way without passing them again. ahash's
+    /// output for given keys can still differ between ahash versions or CPU
+    /// feature sets (such as AES support), so persisted filters should be
+    /// rebuilt if either changes.
+    Keyed {
+        /// Keys for the primary ahash hasher
+        ahash_keys: [u64; 4],
+        /// Seeds for the secondary seahash hasher
+        seahash_seeds: [u64; 4],
+    },
+}
+
+impl HashBackend {
+    /// Get the tag identifying the backend in the binary format.
+    pub(crate) fn tag(&self) -> u8 {
+        match self {
+            HashBackend::AhashSeahash => 0,
+            HashBackend::Seahash => 1,
+            HashBackend::Keyed { .. } => 2,
+        }
+    }
+
+    /// Rebuild a backend from its binary format tag and stored keys.
+    ///
+    /// The keys are only used by [`Keyed`](Self::Keyed).
+    pub(crate) fn from_tag(tag: u8, ahash_keys: [u64; 4], seahash_seeds: [u64; 4]) -> Option<Self> {
+        match tag {
+            0 => Some(HashBackend::AhashSeahash),
+            1 => Some(HashBackend::Seahash),
+            2 => Some(HashBackend::Keyed {
+                ahash_keys,
+                seahash_seeds,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Compute the two independent base hashes of an item.
+    #[inline]
+    pub(crate) fn base_hashes<T: Hash>(&self, item: &T) -> (u64, u64) {
+        match self {
+            HashBackend::AhashSeahash => (
+                HashStrategy::hash_with_ahash(item),
+                HashStrategy::hash_with_seahash(item),
+            ),
+            HashBackend::Seahash => (
+                HashStrategy::hash_with_seeds(item, SEAHASH_SEEDS_1),
+                HashStrategy::hash_with_seeds(item, SEAHASH_SEEDS_2),
+            ),
+            HashBackend::Keyed { .. } => {
+                let (mut h1, mut h2) = self.key_hashers();
+                item.hash(&mut h1);
+                item.hash(&mut h2);
+                (h1.finish(), h2.finish())
+            }
+        }
+    }
+
+    /// Create fresh hashers for the two base hashes of a canonical key.
+    pub(crate) fn key_hashers(&self) -> (PrimaryHasher, seahash::SeaHasher) {
+        match *self {
+            HashBackend::AhashSeahash => (
+                PrimaryHasher::Ahash(HashStrategy::fixed_ahash()),
+                seahash::SeaHasher::new(),
+            ),
+            HashBackend::Seahash => (
+                PrimaryHasher::Seahash(HashStrategy::seeded_seahash(SEAHASH_SEEDS_1)),
+                HashStrategy::seeded_seahash(SEAHASH_SEEDS_2),
+            ),
+            HashBackend::Keyed {
+                ahash_keys,
+                seahash_seeds,
+            } => {
+                let [k0, k1, k2, k3] = ahash_keys;
+                (
+                    PrimaryHasher::Ahash(
+                        ahash::RandomState::with_seeds(k0, k1, k2, k3).build_hasher(),
+                    ),
+                    HashStrategy::seeded_seahash(seahash_seeds),
+                )
+            }
+        }
+    }
+
+    /// Get a short name identifying the backend's hash functions.
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashBackend::AhashSeahash => "ahash+seahash",
+            HashBackend::Seahash => "seahash+seahash",
+            HashBackend::Keyed { .. } => "keyed-ahash+seahash",
+        }
+    }
+}
+
+/// How the k bit indices are derived from the two base hashes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DoubleHashScheme {
+    /// Kirsch-Mitzenmacher double hashing: `h1 + i * h2`
+    #[default]
+    KirschMitzenmacher,
+    /// Enhanced double hashing (Dillinger-Manolios): `h1 + i * h2 + (i^3 - i) / 6`
+    ///
+    /// The cubic term keeps indices from repeating when `h2` shares a factor
+    /// with the bit count, at the cost of a few extra multiplications.
+    Enhanced,
+}
+
+impl DoubleHashScheme {
+    /// Every scheme, in declaration order.
+    pub const ALL: [DoubleHashScheme; 2] = [
+        DoubleHashScheme::KirschMitzenmacher,
+        DoubleHashScheme::Enhanced,
+    ];
+
+    /// Get the tag identifying the scheme in the binary format.
+    pub(crate) fn tag(&self) -> u8 {
+        match self {
+            DoubleHashScheme::KirschMitzenmacher => 0,
+            DoubleHashScheme::Enhanced => 1,
+        }
+    }
+
+    /// Look up a scheme by its binary format tag.
+    pub(crate) fn from_tag(tag: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|scheme| scheme.tag() == tag)
+    }
+
+    /// Get a short name identifying the scheme.
+    pub fn name(&self) -> &'static str {
+        match self {
+            DoubleHashScheme::KirschMitzenmacher => "kirsch-mitzenmacher",
+            DoubleHashScheme::Enhanced => "enhanced-double-hashing",
+        }
+    }
+}
+
+/// Primary hasher for one of the [`HashBackend`] variants.
+#[derive(Clone)]
+pub(crate) enum PrimaryHasher {
+    Ahash(ahash::AHasher),
+    Seahash(seahash::SeaHasher),
+}
+
+impl Hasher for PrimaryHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        match self {
+            Self::Ahash(hasher) => hasher.write(bytes),
+            Self::Seahash(hasher) => hasher.write(bytes),
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        match self {
+            Self::Ahash(hasher) => hasher.finish(),
+            Self::Seahash(hasher) => hasher.finish(),
+        }
+    }
+}
 
 /// Hash strategy that generates multiple hash values from an item.
 ///
@@ -11,12 +228,18 @@ use std::hash::{Hash, Hasher};
 /// h_i(x) = (h1(x) + i * h2(x)) mod m
 ///
 /// This is the proven optimal approach used in production implementations.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct HashStrategy {
     /// Number of hash functions to generate
     num_hashes: usize,
     /// Number of bits in the filter (for modulo operation)
     num_bits: usize,
+    /// Bitmask replacing the modulo when `num_bits` is a power of two
+    mask: Option<u64>,
+    /// Hash functions used for the two base hashes
+    backend: HashBackend,
+    /// How indices are derived from the base hashes
+    scheme: DoubleHashScheme,
 }
 
 impl HashStrategy {
@@ -26,13 +249,48 @@ impl HashStrategy {
     /// * `num_hashes` - Number of hash functions to generate (k)
     /// * `num_bits` - Number of bits in the bloom filter (m)
     pub fn new(num_hashes: usize, num_bits: usize) -> Self {
+        Self::with_backend(num_hashes, num_bits, HashBackend::default())
+    }
+
+    /// Create a new hash strategy using a specific pair of hash functions.
+    ///
+    /// # Arguments
+    /// * `num_hashes` - Number of hash functions to generate (k)
+    /// * `num_bits` - Number of bits in the bloom filter (m)
+    /// * `backend` - Hash functions used for the two base hashes
+    pub fn with_backend(num_hashes: usize, num_bits: usize, backend: HashBackend) -> Self {
         assert!(num_hashes > 0, "num_hashes must be greater than 0");
         assert!(num_bits > 0, "num_bits must be greater than 0");
 
-        Self {
+        // A power-of-two bit count lets us reduce with a cheap bitmask
+        let mask = if num_bits.is_power_of_two() {
+            Some(num_bits as u64 - 1)
+        } else {
+            None
+        };
+
+        let strategy = Self {
             num_hashes,
             num_bits,
-        }
+            mask,
+            backend,
+            scheme: DoubleHashScheme::default(),
+        };
+
+        // Identical base hashers collapse double hashing to a single hash
+        #[cfg(debug_assertions)]
+        assert_hashers_differ(
+            |probe| strategy.base_hashes(probe).0,
+            |probe| strategy.base_hashes(probe).1,
+        );
+
+        strategy
+    }
+
+    /// Use a different scheme to derive indices from the base hashes.
+    pub fn with_scheme(mut self, scheme: DoubleHashScheme) -> Self {
+        self.scheme = scheme;
+        self
     }
 
     /// Generate all hash indices for an item.
@@ -45,11 +303,66 @@ impl HashStrategy {
     /// # Returns
     /// A vector of k unique bit indices
     pub fn hash_indices<T: Hash>(&self, item: &T) -> Vec<usize> {
-        // Compute two independent hashes using different hash functions
-        let h1 = self.hash_with_ahash(item);
-        let h2 = self.hash_with_seahash(item);
+        let (h1, h2) = self.base_hashes(item);
+        self.indices_from_hashes(h1, h2)
+    }
+
+    /// Compute the single index of an item for a strategy with one hash function.
+    ///
+    /// The first double-hashing index is `h1 mod m`, so the secondary hash is
+    /// skipped entirely. Matches `hash_indices(item)[0]`.
+    #[inline]
+    pub fn single_index<T: Hash>(&self, item: &T) -> usize {
+        let h1 = match self.backend {
+            HashBackend::AhashSeahash => Self::hash_with_ahash(item),
+            HashBackend::Seahash => Self::hash_with_seeds(item, SEAHASH_SEEDS_1),
+            HashBackend::Keyed { .. } => {
+                let (mut h1, _) = self.key_hashers();
+                item.hash(&mut h1);
+                h1.finish()
+            }
+        };
+        self.compute_index(h1, 0, 0)
+    }
+
+    /// Compute the two independent base hashes of an item.
+    #[inline]
+    pub(crate) fn base_hashes<T: Hash>(&self, item: &T) -> (u64, u64) {
+        self.backend.base_hashes(item)
+    }
+
+    /// Measure how correlated the two base hashes are across a sample.
+    ///
+    /// Returns the absolute Spearman rank correlation between `h1` and `h2`,
+    /// from 0.0 (no monotonic relationship) to 1.0 (one hash determines the
+    /// other's ordering). Double hashing assumes independent base hashes, so
+    /// a score well above zero points to a misconfigured backend.
+    ///
+    /// Returns 0.0 for samples with fewer than two items.
+    pub fn independence_score<T: Hash>(&self, sample: &[T]) -> f64 {
+        let (h1, h2): (Vec<u64>, Vec<u64>) =
+            sample.iter().map(|item| self.base_hashes(item)).unzip();
+        rank_correlation(&h1, &h2).abs()
+    }
+
+    /// Generate all hash indices for a key using its canonical encoding.
+    ///
+    /// Unlike [`hash_indices`](Self::hash_indices), keys that encode to the
+    /// same bytes (such as `1u32` and `1u64`) produce the same indices.
+    pub fn key_indices<K: BloomKey + ?Sized>(&self, key: &K) -> Vec<usize> {
+        let (mut h1, mut h2) = self.key_hashers();
+        key.write_key(&mut h1, &mut h2);
 
-        // Generate k hash values using standard double hashing
+        self.indices_from_hashes(h1.finish(), h2.finish())
+    }
+
+    /// Create fresh hashers for the two base hashes of a canonical key.
+    pub(crate) fn key_hashers(&self) -> (PrimaryHasher, seahash::SeaHasher) {
+        self.backend.key_hashers()
+    }
+
+    /// Generate k hash values from two base hashes using standard double hashing.
+    pub(crate) fn indices_from_hashes(&self, h1: u64, h2: u64) -> Vec<usize> {
         (0..self.num_hashes)
             .map(|i| self.compute_index(h1, h2, i))
             .collect()
@@ -57,38 +370,283 @@ impl HashStrategy {
 
     /// Hash an item using ahash (primary hash function).
     #[inline]
-    fn hash_with_ahash<T: Hash>(&self, item: &T) -> u64 {
-        let mut hasher = ahash::AHasher::default();
+    fn hash_with_ahash<T: Hash>(item: &T) -> u64 {
+        let mut hasher = Self::fixed_ahash();
         item.hash(&mut hasher);
         hasher.finish()
     }
 
+    /// Create an ahash hasher keyed with the crate's fixed keys.
+    #[inline]
+    fn fixed_ahash() -> ahash::AHasher {
+        let [k0, k1, k2, k3] = AHASH_KEYS;
+        ahash::RandomState::with_seeds(k0, k1, k2, k3).build_hasher()
+    }
+
     /// Hash an item using seahash (secondary hash function).
     #[inline]
-    fn hash_with_seahash<T: Hash>(&self, item: &T) -> u64 {
+    fn hash_with_seahash<T: Hash>(item: &T) -> u64 {
         let mut hasher = seahash::SeaHasher::new();
         item.hash(&mut hasher);
         hasher.finish()
     }
 
-    /// Compute the i-th hash index using standard double hashing.
+    /// Hash an item using seahash keyed with the given seeds.
+    #[inline]
+    fn hash_with_seeds<T: Hash>(item: &T, seeds: [u64; 4]) -> u64 {
+        let mut hasher = Self::seeded_seahash(seeds);
+        item.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Create a seahash hasher keyed with the given seeds.
+    #[inline]
+    fn seeded_seahash(seeds: [u64; 4]) -> seahash::SeaHasher {
+        seahash::SeaHasher::with_seeds(seeds[0], seeds[1], seeds[2], seeds[3])
+    }
+
+    /// Compute the i-th hash index using the strategy's double hashing scheme.
     ///
     /// Formula: (h1 + i * h2) mod m
     ///
-    /// This is the standard Kirsch-Mitzenmacher double hashing approach.
+    /// This is the standard Kirsch-Mitzenmacher double hashing approach;
+    /// [`DoubleHashScheme::Enhanced`] adds `(i^3 - i) / 6` before the modulo.
+    /// When m is a power of two the modulo is replaced by `& (m - 1)`,
+    /// which yields the same index without a division.
     #[inline]
-    fn compute_index(&self, h1: u64, h2: u64, i: usize) -> usize {
+    pub(crate) fn compute_index(&self, h1: u64, h2: u64, i: usize) -> usize {
         let i_u64 = i as u64;
 
         // Standard double hashing: h1 + i*h2
-        let combined = h1.wrapping_add(i_u64.wrapping_mul(h2));
+        let mut combined = h1.wrapping_add(i_u64.wrapping_mul(h2));
+        if self.scheme == DoubleHashScheme::Enhanced {
+            let cubic = i_u64.wrapping_mul(i_u64).wrapping_mul(i_u64);
+            combined = combined.wrapping_add(cubic.wrapping_sub(i_u64) / 6);
+        }
 
-        // Take modulo to get index within bit array
-        (combined % self.num_bits as u64) as usize
+        // Take modulo (or mask) to get index within bit array
+        match self.mask {
+            Some(mask) => (combined & mask) as usize,
+            None => (combined % self.num_bits as u64) as usize,
+        }
     }
 
     /// Get the number of hash functions this strategy generates.
     pub fn num_hashes(&self) -> usize {
         self.num_hashes
     }
+
+    /// Get the hash functions used for the two base hashes.
+    pub fn backend(&self) -> HashBackend {
+        self.backend
+    }
+
+    /// Get the scheme used to derive indices from the base hashes.
+    pub fn scheme(&self) -> DoubleHashScheme {
+        self.scheme
+    }
+
+    /// Time computing the indices of every item without touching a bit array.
+    ///
+    /// Isolates hashing cost from memory-access cost when profiling inserts
+    /// and queries. Results are passed through `black_box` so the work is not
+    /// optimized away.
+    pub fn bench_hash_only<T: Hash>(&self, items: &[T]) -> Duration {
+        let start = Instant::now();
+        for item in items {
+            black_box(self.hash_indices(black_box(item)));
+        }
+        start.elapsed()
+    }
+
+    /// Check whether indices are reduced with a bitmask instead of modulo.
+    pub fn uses_mask(&self) -> bool {
+        self.mask.is_some()
+    }
+}
+
+/// Pick the shard an item belongs to, out of `num_shards`.
+///
+/// Uses seahash keyed with seeds of its own, so the choice of shard is
+/// independent of where the item lands within a filter.
+///
+/// # Panics
+/// Panics if `num_shards` is 0
+pub fn shard_of<T: Hash>(item: &T, num_shards: usize) -> usize {
+    assert!(num_shards > 0, "num_shards must be greater than 0");
+
+    let hash = HashStrategy::hash_with_seeds(item, SHARD_SEEDS);
+    (hash % num_shards as u64) as usize
+}
+
+/// Panic if two base hashers agree on every probe value.
+///
+/// Checked in debug builds only, when a [`HashStrategy`] is created.
+#[cfg(debug_assertions)]
+fn assert_hashers_differ(h1: impl Fn(&u64) -> u64, h2: impl Fn(&u64) -> u64) {
+    assert!(
+        INDEPENDENCE_PROBES
+            .iter()
+            .any(|probe| h1(probe) != h2(probe)),
+        "base hashers are identical; every item would set a single bit"
+    );
+}
+
+/// Spearman rank correlation of two equally long sequences.
+///
+/// Ties are ranked by position, which is adequate for 64-bit hash outputs.
+fn rank_correlation(a: &[u64], b: &[u64]) -> f64 {
+    let n = a.len();
+    if n < 2 {
+        return 0.0;
+    }
+
+    let ranks = |values: &[u64]| {
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by_key(|&i| values[i]);
+
+        let mut ranks = vec![0usize; n];
+        for (rank, i) in order.into_iter().enumerate() {
+            ranks[i] = rank;
+        }
+        ranks
+    };
+
+    // rho = 1 - 6 * sum(d^2) / (n * (n^2 - 1))
+    let sum_d2: f64 = ranks(a)
+        .iter()
+        .zip(ranks(b))
+        .map(|(&ra, rb)| (ra as f64 - rb as f64).powi(2))
+        .sum();
+    let n = n as f64;
+    1.0 - 6.0 * sum_d2 / (n * (n * n - 1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backends_pass_independence_check() {
+        let keyed = HashBackend::Keyed {
+            ahash_keys: [1, 2, 3, 4],
+            seahash_seeds: [5, 6, 7, 8],
+        };
+        for backend in [HashBackend::AhashSeahash, HashBackend::Seahash, keyed] {
+            HashStrategy::with_backend(7, 1000, backend);
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "base hashers are identical")]
+    fn test_identical_hashers_fail_independence_check() {
+        assert_hashers_differ(
+            |probe| HashStrategy::hash_with_seeds(probe, SEAHASH_SEEDS_1),
+            |probe| HashStrategy::hash_with_seeds(probe, SEAHASH_SEEDS_1),
+        );
+    }
+
+    #[test]
+    fn test_mask_matches_modulo() {
+        let strategy = HashStrategy::new(7, 1 << 14);
+        assert!(strategy.uses_mask());
+
+        let num_bits = 1u64 << 14;
+        for item in 0..1000u64 {
+            let h1 = HashStrategy::hash_with_ahash(&item);
+            let h2 = HashStrategy::hash_with_seahash(&item);
+            for i in 0..7 {
+                let expected = (h1.wrapping_add((i as u64).wrapping_mul(h2)) % num_bits) as usize;
+                assert_eq!(strategy.compute_index(h1, h2, i), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_enhanced_scheme_adds_cubic_offset() {
+        let km = HashStrategy::new(7, 9586);
+        let enhanced = HashStrategy::new(7, 9586).with_scheme(DoubleHashScheme::Enhanced);
+        assert_eq!(enhanced.scheme(), DoubleHashScheme::Enhanced);
+
+        for item in 0..1000u64 {
+            let (h1, h2) = km.base_hashes(&item);
+            for i in 0..7u64 {
+                let combined = h1
+                    .wrapping_add(i.wrapping_mul(h2))
+                    .wrapping_add((i * i * i - i) / 6);
+                let expected = (combined % 9586) as usize;
+                assert_eq!(enhanced.compute_index(h1, h2, i as usize), expected);
+            }
+
+            // The first two offsets are zero, so those indices agree
+            assert_eq!(
+                km.hash_indices(&item)[..2],
+                enhanced.hash_indices(&item)[..2]
+            );
+        }
+    }
+
+    #[test]
+    fn test_single_index_matches_first_index() {
+        for backend in [HashBackend::AhashSeahash, HashBackend::Seahash] {
+            let strategy = HashStrategy::with_backend(1, 9586, backend);
+            for item in 0..1000u64 {
+                assert_eq!(
+                    strategy.single_index(&item),
+                    strategy.hash_indices(&item)[0]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_independence_score() {
+        let sample: Vec<String> = (0..10_000).map(|i| format!("key-{}", i)).collect();
+        let keyed = HashBackend::Keyed {
+            ahash_keys: [1, 2, 3, 4],
+            seahash_seeds: SEAHASH_SEEDS_2,
+        };
+
+        for backend in [HashBackend::AhashSeahash, HashBackend::Seahash, keyed] {
+            let strategy = HashStrategy::with_backend(7, 9586, backend);
+            let score = strategy.independence_score(&sample);
+            assert!(score < 0.05, "{:?} scored {}", backend, score);
+        }
+    }
+
+    #[test]
+    fn test_rank_correlation_detects_dependence() {
+        let values: Vec<u64> = (0..1000).map(|i| i * 7919).collect();
+        let reversed: Vec<u64> = values.iter().rev().copied().collect();
+
+        assert!((rank_correlation(&values, &values) - 1.0).abs() < 1e-9);
+        assert!((rank_correlation(&values, &reversed) + 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_shard_of_spreads_items() {
+        let mut counts = [0usize; 8];
+        for i in 0..8000u32 {
+            counts[shard_of(&i, 8)] += 1;
+        }
+        assert!(
+            counts.iter().all(|&c| (800..1200).contains(&c)),
+            "{:?}",
+            counts
+        );
+    }
+
+    #[test]
+    fn test_bench_hash_only() {
+        let strategy = HashStrategy::new(7, 9586);
+        let items: Vec<u64> = (0..10_000).collect();
+        assert!(strategy.bench_hash_only(&items) > Duration::ZERO);
+    }
+
+    #[test]
+    fn test_non_power_of_two_uses_modulo() {
+        let strategy = HashStrategy::new(7, 9586);
+        assert!(!strategy.uses_mask());
+    }
 }
@@ -0,0 +1,117 @@
+//! Bloom filter that answers exactly while the set is small.
+//!
+//! Alongside the bloom bits, [`HybridBloom`] keeps the base hash pair of every
+//! distinct item until the set grows past a threshold. Until then `contains`
+//! compares pairs instead of testing bits, so there are no false positives
+//! beyond a 128-bit hash collision.
+
+use std::hash::Hash;
+
+use crate::filter::PrecisionBloom;
+use crate::params::BloomParameters;
+
+/// Default number of distinct items answered exactly.
+const DEFAULT_EXACT_THRESHOLD: usize = 64;
+
+/// A bloom filter with an exact fast path for small sets.
+///
+/// Every insert also sets the bloom bits, so when the exact set outgrows the
+/// threshold it is simply dropped and queries continue against bits that
+/// already hold every item.
+///
+/// # Example
+/// ```
+/// use bloom_filter_rs::HybridBloom;
+///
+/// let mut filter = HybridBloom::with_capacity(1000, 0.01);
+/// filter.insert(&"hello");
+///
+/// assert!(filter.is_exact());
+/// assert!(filter.contains(&"hello"));
+/// assert!(!filter.contains(&"world"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct HybridBloom {
+    /// Bloom filter holding every inserted item
+    bloom: PrecisionBloom,
+    /// Base hash pairs of the distinct items, until the threshold is passed
+    exact: Option<Vec<(u64, u64)>>,
+    /// Largest number of distinct items kept exactly
+    threshold: usize,
+}
+
+impl HybridBloom {
+    /// Create a hybrid filter that answers exactly for up to `threshold`
+    /// distinct items.
+    pub fn new(params: BloomParameters, threshold: usize) -> Self {
+        Self {
+            bloom: PrecisionBloom::new(params),
+            exact: Some(Vec::with_capacity(threshold.min(DEFAULT_EXACT_THRESHOLD))),
+            threshold,
+        }
+    }
+
+    /// Create a hybrid filter sized for the given number of items and false
+    /// positive rate, answering exactly for up to 64 distinct items.
+    pub fn with_capacity(expected_items: usize, false_positive_rate: f64) -> Self {
+        Self::new(
+            BloomParameters::from_item_count(expected_items, false_positive_rate),
+            DEFAULT_EXACT_THRESHOLD,
+        )
+    }
+
+    /// Insert an item into the filter.
+    ///
+    /// Switches to bloom-only answers once more than `threshold` distinct
+    /// items have been inserted.
+    pub fn insert<T: Hash>(&mut self, item: &T) {
+        let (h1, h2) = self.bloom.item_base_hashes(item);
+        self.bloom.insert_base_hashes(h1, h2);
+
+        if let Some(exact) = &mut self.exact {
+            if !exact.contains(&(h1, h2)) {
+                if exact.len() == self.threshold {
+                    self.exact = None;
+                } else {
+                    exact.push((h1, h2));
+                }
+            }
+        }
+    }
+
+    /// Check if an item might be in the filter.
+    ///
+    /// Exact while [`is_exact`](Self::is_exact) holds.
+    pub fn contains<T: Hash>(&self, item: &T) -> bool {
+        let (h1, h2) = self.bloom.item_base_hashes(item);
+        match &self.exact {
+            Some(exact) => exact.contains(&(h1, h2)),
+            None => self.bloom.contains_base_hashes(h1, h2),
+        }
+    }
+
+    /// Check whether queries are still answered from the exact set.
+    pub fn is_exact(&self) -> bool {
+        self.exact.is_some()
+    }
+
+    /// Get the largest number of distinct items answered exactly.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Get the number of items inserted into the filter.
+    pub fn len(&self) -> usize {
+        self.bloom.len()
+    }
+
+    /// Check if the filter is empty (no items inserted).
+    pub fn is_empty(&self) -> bool {
+        self.bloom.is_empty()
+    }
+
+    /// Get the underlying bloom filter.
+    pub fn bloom(&self) -> &PrecisionBloom {
+        &self.bloom
+    }
+}
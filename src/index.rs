@@ -0,0 +1,118 @@
+//! Two-level index over many sharded bloom filters.
+//!
+//! A single summary filter holds every key, and each key is also stored in
+//! exactly one detail shard chosen by [`shard_of`]. Queries check the summary
+//! first, so most absent keys are rejected without touching any shard.
+
+use std::hash::Hash;
+
+use crate::filter::PrecisionBloom;
+use crate::hash::shard_of;
+
+/// A filter-of-filters: a summary filter in front of sharded detail filters.
+///
+/// Shards count their queries, so [`shard_queries`](Self::shard_queries)
+/// reports how often the summary let a lookup through.
+///
+/// # Example
+/// ```
+/// use bloom_filter_rs::BloomIndex;
+///
+/// let mut index = BloomIndex::new(16, 1000, 0.01);
+/// index.insert(&"hello");
+///
+/// assert!(index.contains(&"hello"));
+/// assert!(!index.contains(&"world"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct BloomIndex {
+    /// Filter over every key, checked before any shard
+    summary: PrecisionBloom,
+    /// Detail filters, each holding the keys routed to it
+    shards: Vec<PrecisionBloom>,
+}
+
+impl BloomIndex {
+    /// Create an index with `num_shards` shards of `items_per_shard` capacity.
+    ///
+    /// The summary is sized for every shard's items combined, and all filters
+    /// use the same target false positive rate.
+    ///
+    /// # Panics
+    /// Panics if `num_shards` or `items_per_shard` is 0, or if the false
+    /// positive rate is not between 0 and 1.
+    pub fn new(num_shards: usize, items_per_shard: usize, false_positive_rate: f64) -> Self {
+        assert!(num_shards > 0, "num_shards must be greater than 0");
+
+        let summary =
+            PrecisionBloom::with_capacity(num_shards * items_per_shard, false_positive_rate);
+        let shards = (0..num_shards)
+            .map(|_| {
+                PrecisionBloom::with_capacity(items_per_shard, false_positive_rate)
+                    .with_query_counting(true)
+            })
+            .collect();
+
+        Self { summary, shards }
+    }
+
+    /// Insert an item into the summary and its shard.
+    ///
+    /// Returns `true` if the item was definitely not in its shard before.
+    pub fn insert<T: Hash>(&mut self, item: &T) -> bool {
+        self.summary.insert(item);
+
+        let shard = shard_of(item, self.shards.len());
+        self.shards[shard].insert(item)
+    }
+
+    /// Check if an item might be in the index.
+    ///
+    /// The shard is only consulted if the summary reports the item present.
+    pub fn contains<T: Hash>(&self, item: &T) -> bool {
+        if !self.summary.contains(item) {
+            return false;
+        }
+
+        let shard = shard_of(item, self.shards.len());
+        self.shards[shard].contains(item)
+    }
+
+    /// Get the shard an item is routed to.
+    pub fn shard_for<T: Hash>(&self, item: &T) -> usize {
+        shard_of(item, self.shards.len())
+    }
+
+    /// Get a shard by position.
+    pub fn shard(&self, index: usize) -> &PrecisionBloom {
+        &self.shards[index]
+    }
+
+    /// Get the summary filter.
+    pub fn summary(&self) -> &PrecisionBloom {
+        &self.summary
+    }
+
+    /// Get the number of shards.
+    pub fn num_shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Get the total number of queries that reached a shard.
+    pub fn shard_queries(&self) -> usize {
+        self.shards
+            .iter()
+            .map(PrecisionBloom::queries_performed)
+            .sum()
+    }
+
+    /// Get the number of items inserted into the index.
+    pub fn len(&self) -> usize {
+        self.summary.len()
+    }
+
+    /// Check if the index is empty (no items inserted).
+    pub fn is_empty(&self) -> bool {
+        self.summary.is_empty()
+    }
+}
@@ -0,0 +1,213 @@
+//! Canonical key encoding for bloom filters.
+//!
+//! The standard library's `Hash` framing is type-dependent: `1u32` and `1u64`
+//! hash differently, and `str` appends a terminator byte. [`BloomKey`] feeds
+//! both hashers a canonical byte encoding instead, so logically equal keys
+//! always map to the same bits. With the default backend's fixed keys, that
+//! holds across processes and platforms too, subject to the ahash caveats on
+//! [`HashBackend::Keyed`](crate::HashBackend::Keyed).
+
+use std::fmt;
+use std::hash::Hasher;
+
+use crate::filter::PrecisionBloom;
+use crate::hash::PrimaryHasher;
+
+/// Block size used when feeding byte keys to the primary hasher.
+///
+/// ahash's output depends on how input is split across `write` calls, so
+/// bytes are always fed in blocks of this size to keep the encoding stable.
+pub(crate) const KEY_BLOCK: usize = 32;
+
+/// A key with a canonical byte encoding for bloom filter hashing.
+///
+/// Implemented for integers (widened to 8 little-endian bytes, or 16 for
+/// 128-bit types), strings (raw UTF-8 bytes) and byte slices (raw bytes).
+/// Unsigned and signed integers are widened to `u64` and `i64` respectively,
+/// so `1u32` and `1u64` are the same key.
+pub trait BloomKey {
+    /// Write the canonical encoding of this key into both hashers.
+    fn write_key(&self, h1: &mut impl Hasher, h2: &mut impl Hasher);
+}
+
+/// Write raw bytes into both hashers using the canonical block framing.
+pub(crate) fn write_key_bytes(bytes: &[u8], h1: &mut impl Hasher, h2: &mut impl Hasher) {
+    for block in bytes.chunks(KEY_BLOCK) {
+        h1.write(block);
+    }
+    h2.write(bytes);
+}
+
+/// Incremental hasher for byte keys that arrive in chunks.
+///
+/// Obtained from [`PrecisionBloom::key_hasher`]. Feeding a key's bytes through
+/// [`update`](Self::update) in any number of chunks yields the same bits as
+/// passing the concatenated bytes to [`PrecisionBloom::insert_key`], so large
+/// keys can be hashed without buffering them whole.
+///
+/// # Example
+/// ```
+/// use bloom_filter_rs::PrecisionBloom;
+///
+/// let mut filter = PrecisionBloom::with_capacity(100, 0.01);
+///
+/// let mut hasher = filter.key_hasher();
+/// hasher.update(b"hello ");
+/// hasher.update(b"world");
+/// hasher.finish_insert(&mut filter);
+///
+/// assert!(filter.contains_key(b"hello world".as_slice()));
+/// ```
+#[derive(Clone)]
+pub struct BloomKeyHasher {
+    /// Primary hasher, fed in blocks of `KEY_BLOCK` bytes
+    h1: PrimaryHasher,
+    /// Secondary hasher, fed every chunk directly
+    h2: seahash::SeaHasher,
+    /// Bytes not yet written to the primary hasher
+    pending: Vec<u8>,
+}
+
+impl BloomKeyHasher {
+    /// Create a streaming hasher from fresh base hashers.
+    pub(crate) fn new(h1: PrimaryHasher, h2: seahash::SeaHasher) -> Self {
+        Self {
+            h1,
+            h2,
+            pending: Vec::with_capacity(KEY_BLOCK),
+        }
+    }
+
+    /// Feed the next chunk of the key.
+    pub fn update(&mut self, mut chunk: &[u8]) {
+        self.h2.write(chunk);
+
+        // Top up a partially filled block first
+        if !self.pending.is_empty() {
+            let take = (KEY_BLOCK - self.pending.len()).min(chunk.len());
+            self.pending.extend_from_slice(&chunk[..take]);
+            chunk = &chunk[take..];
+
+            if self.pending.len() < KEY_BLOCK {
+                return;
+            }
+            self.h1.write(&self.pending);
+            self.pending.clear();
+        }
+
+        let mut blocks = chunk.chunks_exact(KEY_BLOCK);
+        for block in &mut blocks {
+            self.h1.write(block);
+        }
+        self.pending.extend_from_slice(blocks.remainder());
+    }
+
+    /// Finish hashing and return the two base hashes.
+    fn finish(mut self) -> (u64, u64) {
+        if !self.pending.is_empty() {
+            self.h1.write(&self.pending);
+        }
+        (self.h1.finish(), self.h2.finish())
+    }
+
+    /// Finish hashing and insert the key into the filter.
+    ///
+    /// Returns `true` if the key was definitely not in the filter before.
+    ///
+    /// # Panics
+    /// Panics if the filter is at capacity and its overfill behavior is
+    /// [`OverfillBehavior::Reject`](crate::OverfillBehavior::Reject).
+    pub fn finish_insert(self, filter: &mut PrecisionBloom) -> bool {
+        let (h1, h2) = self.finish();
+        filter.insert_base_hashes(h1, h2)
+    }
+
+    /// Finish hashing and check if the key might be in the filter.
+    pub fn finish_contains(self, filter: &PrecisionBloom) -> bool {
+        let (h1, h2) = self.finish();
+        filter.contains_base_hashes(h1, h2)
+    }
+}
+
+impl fmt::Debug for BloomKeyHasher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BloomKeyHasher")
+            .field("pending", &self.pending.len())
+            .finish_non_exhaustive()
+    }
+}
+
+macro_rules! impl_unsigned_key {
+    ($($t:ty),*) => {
+        $(
+            impl BloomKey for $t {
+                fn write_key(&self, h1: &mut impl Hasher, h2: &mut impl Hasher) {
+                    write_key_bytes(&(*self as u64).to_le_bytes(), h1, h2);
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_signed_key {
+    ($($t:ty),*) => {
+        $(
+            impl BloomKey for $t {
+                fn write_key(&self, h1: &mut impl Hasher, h2: &mut impl Hasher) {
+                    write_key_bytes(&(*self as i64).to_le_bytes(), h1, h2);
+                }
+            }
+        )*
+    };
+}
+
+impl_unsigned_key!(u8, u16, u32, u64, usize);
+impl_signed_key!(i8, i16, i32, i64, isize);
+
+impl BloomKey for u128 {
+    fn write_key(&self, h1: &mut impl Hasher, h2: &mut impl Hasher) {
+        write_key_bytes(&self.to_le_bytes(), h1, h2);
+    }
+}
+
+impl BloomKey for i128 {
+    fn write_key(&self, h1: &mut impl Hasher, h2: &mut impl Hasher) {
+        write_key_bytes(&self.to_le_bytes(), h1, h2);
+    }
+}
+
+impl BloomKey for str {
+    fn write_key(&self, h1: &mut impl Hasher, h2: &mut impl Hasher) {
+        write_key_bytes(self.as_bytes(), h1, h2);
+    }
+}
+
+impl BloomKey for String {
+    fn write_key(&self, h1: &mut impl Hasher, h2: &mut impl Hasher) {
+        write_key_bytes(self.as_bytes(), h1, h2);
+    }
+}
+
+impl BloomKey for [u8] {
+    fn write_key(&self, h1: &mut impl Hasher, h2: &mut impl Hasher) {
+        write_key_bytes(self, h1, h2);
+    }
+}
+
+impl<const N: usize> BloomKey for [u8; N] {
+    fn write_key(&self, h1: &mut impl Hasher, h2: &mut impl Hasher) {
+        write_key_bytes(self, h1, h2);
+    }
+}
+
+impl BloomKey for Vec<u8> {
+    fn write_key(&self, h1: &mut impl Hasher, h2: &mut impl Hasher) {
+        write_key_bytes(self, h1, h2);
+    }
+}
+
+impl<T: BloomKey + ?Sized> BloomKey for &T {
+    fn write_key(&self, h1: &mut impl Hasher, h2: &mut impl Hasher) {
+        (**self).write_key(h1, h2);
+    }
+}
@@ -0,0 +1,108 @@
+//! Bloom filter that defers allocating its bits until the first insert.
+//!
+//! A map of many mostly-unused filters pays for every bit array up front.
+//! [`LazyBloom`] holds only its parameters until an item is inserted, so
+//! filters that never receive an item cost no bit-array memory.
+
+use std::hash::Hash;
+
+use crate::filter::PrecisionBloom;
+use crate::params::BloomParameters;
+
+/// A bloom filter whose bit array is allocated on first insert.
+///
+/// # Example
+/// ```
+/// use bloom_filter_rs::LazyBloom;
+///
+/// let mut filter = LazyBloom::with_capacity(1000, 0.01);
+/// assert!(!filter.is_allocated());
+/// assert!(!filter.contains(&"hello"));
+///
+/// filter.insert(&"hello");
+/// assert!(filter.is_allocated());
+/// assert!(filter.contains(&"hello"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct LazyBloom {
+    /// Parameters used to build the filter
+    params: BloomParameters,
+    /// The filter, once the first item has been inserted
+    filter: Option<PrecisionBloom>,
+}
+
+impl LazyBloom {
+    /// Create a lazy filter with the given parameters.
+    ///
+    /// The parameters are validated now, so an invalid configuration is
+    /// reported here rather than on first insert.
+    pub fn new(mut params: BloomParameters) -> Self {
+        params.validate().expect("Invalid parameters");
+        params.clamp_hashes();
+
+        Self {
+            params,
+            filter: None,
+        }
+    }
+
+    /// Create a lazy filter sized for the given number of items and false
+    /// positive rate.
+    pub fn with_capacity(expected_items: usize, false_positive_rate: f64) -> Self {
+        Self::new(BloomParameters::from_item_count(
+            expected_items,
+            false_positive_rate,
+        ))
+    }
+
+    /// Insert an item, allocating the bit array if this is the first insert.
+    ///
+    /// Returns `true` if the item was definitely not in the filter before.
+    pub fn insert<T: Hash>(&mut self, item: &T) -> bool {
+        let params = self.params;
+        self.filter
+            .get_or_insert_with(|| PrecisionBloom::new(params))
+            .insert(item)
+    }
+
+    /// Check if an item might be in the filter.
+    ///
+    /// Returns `false` without hashing if nothing has been inserted yet.
+    pub fn contains<T: Hash>(&self, item: &T) -> bool {
+        self.filter
+            .as_ref()
+            .is_some_and(|filter| filter.contains(item))
+    }
+
+    /// Check whether the bit array has been allocated.
+    pub fn is_allocated(&self) -> bool {
+        self.filter.is_some()
+    }
+
+    /// Get the heap memory used by the bit array, in bytes.
+    ///
+    /// Zero until the first insert.
+    pub fn memory_usage(&self) -> usize {
+        self.filter.as_ref().map_or(0, PrecisionBloom::memory_usage)
+    }
+
+    /// Get the number of items inserted into the filter.
+    pub fn len(&self) -> usize {
+        self.filter.as_ref().map_or(0, PrecisionBloom::len)
+    }
+
+    /// Check if the filter is empty (no items inserted).
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the parameters of this filter.
+    pub fn parameters(&self) -> &BloomParameters {
+        &self.params
+    }
+
+    /// Get the underlying filter, if it has been allocated.
+    pub fn filter(&self) -> Option<&PrecisionBloom> {
+        self.filter.as_ref()
+    }
+}
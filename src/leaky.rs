@@ -0,0 +1,133 @@
+//! Bloom filter that trades a bounded false negative rate for memory.
+//!
+//! Each insert stores the item only with probability `1 - max_fn_rate`, so
+//! the bit array is sized for that fraction of the expected items. Stored
+//! items are always found; skipped items are found only as false positives.
+//!
+//! The saving is about the budget itself: a 5% false negative budget saves
+//! about 5% of the bits. That is close to the limit for any design, since a
+//! filter that may forget a fraction `f` of the items still has to encode
+//! the other `1 - f` at the target false positive rate. Setting each of an
+//! item's bits with some probability instead saves far less, because one
+//! unset bit out of `k` is enough to lose the item.
+
+use std::hash::Hash;
+
+use rand::Rng;
+
+use crate::filter::PrecisionBloom;
+use crate::params::BloomParameters;
+
+/// A bloom filter sized for an allowed false negative rate.
+///
+/// Unlike a classic bloom filter, an inserted item may be reported absent.
+/// Each insert is skipped with probability equal to the configured budget,
+/// and a skipped item is still found if it happens to be a false positive,
+/// so the expected false negative rate is slightly below the budget.
+///
+/// # Example
+/// ```
+/// use bloom_filter_rs::{BloomParameters, LeakyBloom};
+///
+/// let classic = BloomParameters::from_item_count(10_000, 0.01);
+/// let leaky = LeakyBloom::with_fn_budget(10_000, 0.01, 0.2);
+///
+/// // A 20% budget saves about 20% of the bits
+/// let ratio = leaky.parameters().num_bits as f64 / classic.num_bits as f64;
+/// assert!((0.79..0.81).contains(&ratio));
+/// ```
+#[derive(Debug, Clone)]
+pub struct LeakyBloom {
+    /// Underlying filter holding the stored items
+    inner: PrecisionBloom,
+    /// Probability that an insert stores the item
+    keep_probability: f64,
+    /// Configured false negative rate
+    fn_budget: f64,
+    /// Number of inserts, including skipped ones
+    items_inserted: usize,
+}
+
+impl LeakyBloom {
+    /// Create a filter for `expected_items` at `false_positive_rate` whose
+    /// expected false negative rate stays within `max_fn_rate`.
+    ///
+    /// The bit array is sized for `(1 - max_fn_rate) * expected_items` items
+    /// at the target rate, with the classic optimal hash count, so it is a
+    /// factor `1 - max_fn_rate` smaller than a classic filter.
+    ///
+    /// # Panics
+    /// Panics if `expected_items` is 0, `false_positive_rate` is not between
+    /// 0 and 1, or `max_fn_rate` is not in `[0, 1)`.
+    pub fn with_fn_budget(
+        expected_items: usize,
+        false_positive_rate: f64,
+        max_fn_rate: f64,
+    ) -> Self {
+        assert!(
+            (0.0..1.0).contains(&max_fn_rate),
+            "max_fn_rate must be in [0, 1)"
+        );
+        assert!(expected_items > 0, "expected_items must be greater than 0");
+
+        let keep_probability = 1.0 - max_fn_rate;
+        let stored_items = (keep_probability * expected_items as f64).ceil() as usize;
+
+        let params = BloomParameters {
+            expected_items,
+            ..BloomParameters::from_item_count(stored_items.max(1), false_positive_rate)
+        };
+
+        Self {
+            inner: PrecisionBloom::new(params),
+            keep_probability,
+            fn_budget: max_fn_rate,
+            items_inserted: 0,
+        }
+    }
+
+    /// Insert an item, storing it with probability `1 - max_fn_rate`.
+    pub fn insert<T: Hash>(&mut self, item: &T, rng: &mut impl Rng) {
+        self.items_inserted += 1;
+        if rng.gen_bool(self.keep_probability) {
+            self.inner.insert(item);
+        }
+    }
+
+    /// Check if an item might be in the filter.
+    ///
+    /// May return `false` for an inserted item, within the false negative budget.
+    pub fn contains<T: Hash>(&self, item: &T) -> bool {
+        self.inner.contains(item)
+    }
+
+    /// Get the probability that an insert stores the item.
+    pub fn keep_probability(&self) -> f64 {
+        self.keep_probability
+    }
+
+    /// Get the configured false negative rate.
+    pub fn false_negative_budget(&self) -> f64 {
+        self.fn_budget
+    }
+
+    /// Get the number of items inserted into the filter, including skipped ones.
+    pub fn len(&self) -> usize {
+        self.items_inserted
+    }
+
+    /// Check if the filter is empty (no items inserted).
+    pub fn is_empty(&self) -> bool {
+        self.items_inserted == 0
+    }
+
+    /// Get the heap memory used by the bit array, in bytes.
+    pub fn memory_usage(&self) -> usize {
+        self.inner.memory_usage()
+    }
+
+    /// Get the parameters of this filter.
+    pub fn parameters(&self) -> &BloomParameters {
+        self.inner.parameters()
+    }
+}
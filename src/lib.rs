@@ -117,13 +117,44 @@
 //! ```
 
 mod accuracy;
+#[cfg(feature = "rand")]
+mod aging;
+mod archive;
 mod bit_array;
+mod dual;
+mod error;
 mod filter;
 mod hash;
+mod hybrid;
+mod index;
+mod key;
+mod lazy;
+#[cfg(feature = "rand")]
+mod leaky;
+mod optimized;
 mod params;
+mod small;
 
-pub use accuracy::AccuracyTracker;
+pub use accuracy::{
+    aggregate_stats, fpr_validation_sample_size, AccuracyTracker, AggregateStats, BloomMetrics,
+    FprSample, ValidationReport,
+};
+#[cfg(feature = "rand")]
+pub use aging::AgingBloom;
+pub use archive::{BloomArchive, BloomArchiveWriter};
 pub use bit_array::BitArray;
-pub use filter::PrecisionBloom;
-pub use hash::HashStrategy;
-pub use params::BloomParameters;
+pub use dual::DualBackendBloom;
+pub use error::BloomError;
+pub use filter::{OverfillBehavior, PrecisionBloom, Provenance};
+pub use hash::{shard_of, DoubleHashScheme, HashBackend, HashStrategy};
+pub use hybrid::HybridBloom;
+pub use index::BloomIndex;
+pub use key::{BloomKey, BloomKeyHasher};
+pub use lazy::LazyBloom;
+#[cfg(feature = "rand")]
+pub use leaky::LeakyBloom;
+pub use optimized::OptimizedBloom;
+pub use params::{
+    cost_optimal_fpr, fpr_curve, params_for_latency, required_bytes, BloomParameters, RoundingMode,
+};
+pub use small::{IndexWidth, SmallBloom};
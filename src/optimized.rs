@@ -0,0 +1,98 @@
+//! Query-only bloom filter produced from a finished [`PrecisionBloom`](crate::PrecisionBloom).
+
+use std::hash::Hash;
+
+use crate::bit_array::BitArray;
+use crate::hash::HashStrategy;
+use crate::params::BloomParameters;
+
+/// An immutable bloom filter tuned for queries.
+///
+/// Created with [`PrecisionBloom::into_optimized`](crate::PrecisionBloom::into_optimized). Bit positions are fixed
+/// by the original hashing, so the layout cannot be reordered without the
+/// original items; instead `contains` computes indices lazily and stops at
+/// the first unset bit, with no allocation, no statistics tracking and no
+/// copy-on-write indirection. Absent items, which usually fail on an early
+/// bit, benefit the most.
+///
+/// The conversion is one-way: there is no way to insert into or convert
+/// back from an `OptimizedBloom`.
+///
+/// # Example
+/// ```
+/// use bloom_filter_rs::PrecisionBloom;
+///
+/// let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+/// filter.insert(&"hello");
+///
+/// let optimized = filter.into_optimized();
+/// assert!(optimized.contains(&"hello"));
+/// assert!(!optimized.contains(&"world"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct OptimizedBloom {
+    /// Bit array words, in the same layout as [`BitArray`]
+    words: Box<[u64]>,
+    /// Hash strategy the filter was built with
+    hash_strategy: HashStrategy,
+    /// Parameters of the original filter
+    params: BloomParameters,
+    /// Number of items inserted before conversion
+    items_inserted: usize,
+}
+
+impl OptimizedBloom {
+    /// Assemble an optimized filter from a finished filter's parts.
+    pub(crate) fn new(
+        words: Box<[u64]>,
+        hash_strategy: HashStrategy,
+        params: BloomParameters,
+        items_inserted: usize,
+    ) -> Self {
+        Self {
+            words,
+            hash_strategy,
+            params,
+            items_inserted,
+        }
+    }
+
+    /// Check if an item might be in the filter.
+    ///
+    /// Answers exactly as the original filter's `contains` did.
+    #[inline]
+    pub fn contains<T: Hash>(&self, item: &T) -> bool {
+        let (h1, h2) = self.hash_strategy.base_hashes(item);
+
+        (0..self.params.num_hashes).all(|i| {
+            let index = self.hash_strategy.compute_index(h1, h2, i);
+            let (word_index, bit_index) = BitArray::locate(index);
+            self.words[word_index] & (1u64 << bit_index) != 0
+        })
+    }
+
+    /// Get the number of items inserted before conversion.
+    pub fn len(&self) -> usize {
+        self.items_inserted
+    }
+
+    /// Check if no items were inserted before conversion.
+    pub fn is_empty(&self) -> bool {
+        self.items_inserted == 0
+    }
+
+    /// Get the number of bits in the filter.
+    pub fn num_bits(&self) -> usize {
+        self.params.num_bits
+    }
+
+    /// Get the number of hash functions.
+    pub fn num_hashes(&self) -> usize {
+        self.params.num_hashes
+    }
+
+    /// Get the parameters of the original filter.
+    pub fn parameters(&self) -> &BloomParameters {
+        &self.params
+    }
+}
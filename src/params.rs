@@ -5,6 +5,31 @@
 
 use std::f64;
 
+use crate::error::BloomError;
+
+/// How fractional optimal values are rounded to whole bit and hash counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoundingMode {
+    /// Round up, never undershooting the target rate
+    #[default]
+    Ceil,
+    /// Round to the nearest whole number
+    Round,
+    /// Round down, favoring less memory and fewer hash computations
+    Floor,
+}
+
+impl RoundingMode {
+    /// Apply the rounding mode to a value.
+    fn apply(self, value: f64) -> f64 {
+        match self {
+            RoundingMode::Ceil => value.ceil(),
+            RoundingMode::Round => value.round(),
+            RoundingMode::Floor => value.floor(),
+        }
+    }
+}
+
 /// Parameters for configuring a bloom filter.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct BloomParameters {
@@ -36,6 +61,32 @@ impl BloomParameters {
     /// - k = num_hashes
     ///
     pub fn from_item_count(expected_items: usize, false_positive_rate: f64) -> Self {
+        Self::from_item_count_with_rounding(expected_items, false_positive_rate, RoundingMode::Ceil)
+    }
+
+    /// Calculate parameters like [`from_item_count`](Self::from_item_count), choosing how
+    /// the optimal bit and hash counts are rounded.
+    ///
+    /// `from_item_count` uses [`RoundingMode::Ceil`]. Rounding down saves memory
+    /// and hash computations at the cost of a slightly higher false positive
+    /// rate than requested. Both counts are at least 1.
+    ///
+    /// # Example
+    /// ```
+    /// use bloom_filter_rs::{BloomParameters, RoundingMode};
+    ///
+    /// // The optimal hash count here is about 6.4
+    /// let ceil = BloomParameters::from_item_count_with_rounding(1000, 0.0118, RoundingMode::Ceil);
+    /// let round = BloomParameters::from_item_count_with_rounding(1000, 0.0118, RoundingMode::Round);
+    ///
+    /// assert_eq!(ceil.num_hashes, 7);
+    /// assert_eq!(round.num_hashes, 6);
+    /// ```
+    pub fn from_item_count_with_rounding(
+        expected_items: usize,
+        false_positive_rate: f64,
+        rounding: RoundingMode,
+    ) -> Self {
         assert!(expected_items > 0, "expected_items must be greater than 0");
         assert!(
             false_positive_rate > 0.0 && false_positive_rate < 1.0,
@@ -47,10 +98,11 @@ impl BloomParameters {
 
         // Calculate optimal number of bits: m = -n * ln(p) / (ln(2)^2)
         let ln_2 = f64::ln(2.0);
-        let num_bits = (-n * f64::ln(p) / (ln_2 * ln_2)).ceil() as usize;
+        let num_bits = rounding.apply(-n * f64::ln(p) / (ln_2 * ln_2)) as usize;
+        let num_bits = num_bits.max(1);
 
         // Calculate optimal number of hashes: k = (m/n) * ln(2)
-        let num_hashes = ((num_bits as f64 / n) * ln_2).ceil() as usize;
+        let num_hashes = rounding.apply((num_bits as f64 / n) * ln_2) as usize;
 
         // Ensure at least 1 hash function
         let num_hashes = num_hashes.max(1);
@@ -63,6 +115,28 @@ impl BloomParameters {
         }
     }
 
+    /// Calculate parameters like [`from_item_count`](Self::from_item_count), rounding the
+    /// number of bits up to the next power of two.
+    ///
+    /// A power-of-two bit count lets the hash strategy reduce indices with a
+    /// bitmask instead of a modulo, which is cheaper on the hot path. The hash
+    /// count is kept at the value optimal for the requested rate, so the extra
+    /// bits only lower the false positive rate below the target.
+    ///
+    /// # Arguments
+    /// * `expected_items` - Number of items expected to be inserted (n)
+    /// * `false_positive_rate` - Desired false positive rate (must be between 0 and 1)
+    pub fn from_item_count_pow2(expected_items: usize, false_positive_rate: f64) -> Self {
+        let optimal = Self::from_item_count(expected_items, false_positive_rate);
+
+        // Keep the hash count sized for the target rate so the hot path
+        // does no more work than the modulo configuration
+        Self {
+            num_bits: optimal.num_bits.next_power_of_two(),
+            ..optimal
+        }
+    }
+
     /// Create parameters with explicit bit count and item count, calculating optimal hash count.
     ///
     /// # Arguments
@@ -120,6 +194,156 @@ impl BloomParameters {
         Self::calculate_fpr(self.num_bits, self.num_hashes, actual_items)
     }
 
+    /// Get the largest item count whose theoretical false positive rate stays
+    /// within `max_fpr`.
+    ///
+    /// Inverts p = (1 - e^(-kn/m))^k for n: n = -(m/k) * ln(1 - p^(1/k)).
+    /// Returns `usize::MAX` if `max_fpr` is 1 or more.
+    pub fn max_items_for_fpr(&self, max_fpr: f64) -> usize {
+        if max_fpr >= 1.0 {
+            return usize::MAX;
+        }
+        if max_fpr <= 0.0 {
+            return 0;
+        }
+
+        let m = self.num_bits as f64;
+        let k = self.num_hashes as f64;
+        let mut n = (-(m / k) * f64::ln(1.0 - max_fpr.powf(1.0 / k))).floor() as usize;
+
+        // Guard against rounding error at the boundary
+        while n > 0 && self.actual_fpr(n) > max_fpr {
+            n -= 1;
+        }
+        n
+    }
+
+    /// Estimate how many distinct items produced a given number of set bits.
+    ///
+    /// Formula (Swamidass-Baldi): n ≈ -(m/k) * ln(1 - X/m)
+    ///
+    /// Where X is the number of set bits. Returns infinity when every bit is set,
+    /// since a saturated filter is consistent with any number of items.
+    pub fn estimate_items(&self, set_bits: usize) -> f64 {
+        let m = self.num_bits as f64;
+        let k = self.num_hashes as f64;
+        let x = set_bits.min(self.num_bits) as f64;
+
+        if x >= m {
+            return f64::INFINITY;
+        }
+
+        -(m / k) * f64::ln(1.0 - x / m)
+    }
+
+    /// Get the fraction of extra bits used compared to the unrounded optimum.
+    ///
+    /// The optimum is m = -n * ln(p) / (ln(2)^2) before rounding. Ceiling adds
+    /// less than one bit, which is negligible for typical sizes but a large
+    /// share of a tiny filter. Rounding up to a power of two also shows here.
+    /// Negative if the parameters use fewer bits than the optimum, e.g. with
+    /// [`RoundingMode::Floor`].
+    pub fn ceiling_overhead(&self) -> f64 {
+        let n = self.expected_items as f64;
+        let ln_2 = f64::ln(2.0);
+        let optimal = -n * f64::ln(self.false_positive_rate) / (ln_2 * ln_2);
+
+        (self.num_bits as f64 - optimal) / optimal
+    }
+
+    /// Check whether filters built with these parameters can be combined bit-for-bit.
+    ///
+    /// Only `num_bits` and `num_hashes` affect where items land, so the
+    /// expected item count and target rate may differ.
+    pub fn compatible_with(&self, other: &BloomParameters) -> bool {
+        self.num_bits == other.num_bits && self.num_hashes == other.num_hashes
+    }
+
+    /// Clamp `num_hashes` so it does not exceed `num_bits`.
+    ///
+    /// With more hash functions than bits every item sets every bit, giving a
+    /// 100% false positive rate. The derived constructors never produce such
+    /// parameters, but hand-built ones can; filter constructors clamp them.
+    ///
+    /// Returns `true` if `num_hashes` was lowered.
+    pub fn clamp_hashes(&mut self) -> bool {
+        if self.num_hashes <= self.num_bits {
+            return false;
+        }
+        self.num_hashes = self.num_bits;
+        true
+    }
+
+    /// Format the parameters as a short human-readable config string.
+    ///
+    /// The string looks like `m=9586,k=7,n=1000,p=0.01` and parses back to
+    /// identical parameters with [`from_config_string`](Self::from_config_string).
+    ///
+    /// # Example
+    /// ```
+    /// use bloom_filter_rs::BloomParameters;
+    ///
+    /// let params = BloomParameters::from_item_count(1000, 0.01);
+    /// assert_eq!(params.to_config_string(), "m=9586,k=7,n=1000,p=0.01");
+    /// ```
+    pub fn to_config_string(&self) -> String {
+        format!(
+            "m={},k={},n={},p={}",
+            self.num_bits, self.num_hashes, self.expected_items, self.false_positive_rate
+        )
+    }
+
+    /// Parse parameters from a config string produced by
+    /// [`to_config_string`](Self::to_config_string).
+    ///
+    /// Fields may appear in any order, but each of `m`, `k`, `n` and `p` must
+    /// appear exactly once.
+    ///
+    /// # Errors
+    /// Returns `BloomError::InvalidFormat` if a field is missing, repeated,
+    /// unknown or unparseable, or if the parameters fail validation.
+    pub fn from_config_string(s: &str) -> Result<Self, BloomError> {
+        let invalid = |msg: String| BloomError::InvalidFormat(msg);
+
+        let mut fields: [Option<&str>; 4] = [None; 4];
+        for field in s.trim().split(',') {
+            let (key, value) = field
+                .split_once('=')
+                .ok_or_else(|| invalid(format!("expected key=value, got {:?}", field)))?;
+
+            let slot = match key.trim() {
+                "m" => 0,
+                "k" => 1,
+                "n" => 2,
+                "p" => 3,
+                other => return Err(invalid(format!("unknown config key {:?}", other))),
+            };
+            if fields[slot].replace(value.trim()).is_some() {
+                return Err(invalid(format!("duplicate config key {:?}", key.trim())));
+            }
+        }
+
+        let [Some(m), Some(k), Some(n), Some(p)] = fields else {
+            return Err(invalid("config requires m, k, n and p".to_string()));
+        };
+        let count = |key: &str, value: &str| {
+            value
+                .parse::<usize>()
+                .map_err(|_| invalid(format!("invalid value for {}: {:?}", key, value)))
+        };
+
+        let params = Self {
+            num_bits: count("m", m)?,
+            num_hashes: count("k", k)?,
+            expected_items: count("n", n)?,
+            false_positive_rate: p
+                .parse::<f64>()
+                .map_err(|_| invalid(format!("invalid value for p: {:?}", p)))?,
+        };
+        params.validate().map_err(invalid)?;
+        Ok(params)
+    }
+
     /// Validate parameters for sanity.
     pub fn validate(&self) -> Result<(), String> {
         if self.num_bits == 0 {
@@ -137,3 +361,170 @@ impl BloomParameters {
         Ok(())
     }
 }
+
+/// Calculate parameters that meet a false positive rate with a capped hash count.
+///
+/// Each query costs one hash computation per hash function, so latency-sensitive
+/// callers can cap `num_hashes` at `max_hashes_budget`. If the optimal hash
+/// count fits the budget the optimal parameters are returned unchanged;
+/// otherwise the budget is used and the bit count grows until the rate is met:
+/// m = -k * n / ln(1 - p^(1/k)).
+///
+/// # Arguments
+/// * `expected_items` - Number of items expected to be inserted (n)
+/// * `max_fpr` - Highest acceptable false positive rate (between 0 and 1)
+/// * `max_hashes_budget` - Largest acceptable number of hash functions (k)
+///
+/// # Example
+/// ```
+/// use bloom_filter_rs::{params_for_latency, BloomParameters};
+///
+/// let optimal = BloomParameters::from_item_count(1000, 0.01);
+/// let fast = params_for_latency(1000, 0.01, 4);
+///
+/// assert_eq!(fast.num_hashes, 4);
+/// assert!(fast.num_bits > optimal.num_bits);
+/// ```
+///
+/// # Panics
+/// Panics if `expected_items` or `max_hashes_budget` is 0, or if `max_fpr`
+/// is not between 0 and 1.
+pub fn params_for_latency(
+    expected_items: usize,
+    max_fpr: f64,
+    max_hashes_budget: usize,
+) -> BloomParameters {
+    assert!(
+        max_hashes_budget > 0,
+        "max_hashes_budget must be greater than 0"
+    );
+
+    let optimal = BloomParameters::from_item_count(expected_items, max_fpr);
+    if optimal.num_hashes <= max_hashes_budget {
+        return optimal;
+    }
+
+    let n = expected_items as f64;
+    let k = max_hashes_budget as f64;
+    let mut num_bits = (-k * n / f64::ln(1.0 - max_fpr.powf(1.0 / k))).ceil() as usize;
+
+    // Guard against rounding error at the boundary
+    while BloomParameters::calculate_fpr(num_bits, max_hashes_budget, expected_items) > max_fpr {
+        num_bits += 1;
+    }
+
+    BloomParameters {
+        num_bits,
+        num_hashes: max_hashes_budget,
+        expected_items,
+        false_positive_rate: max_fpr,
+    }
+}
+
+/// Get the bytes of bit storage a filter sized for `expected_items` at
+/// `false_positive_rate` needs, without constructing it.
+///
+/// Bits are stored in 64-bit words, so this is `num_bits` from
+/// [`BloomParameters::from_item_count`] rounded up to a multiple of 64 and
+/// divided by 8, matching [`PrecisionBloom::memory_usage`](crate::PrecisionBloom::memory_usage).
+///
+/// # Example
+/// ```
+/// use bloom_filter_rs::required_bytes;
+///
+/// // 9586 bits round up to 150 words
+/// assert_eq!(required_bytes(1000, 0.01), 1200);
+/// ```
+///
+/// # Panics
+/// Panics if `expected_items` is 0 or `false_positive_rate` is not between 0 and 1.
+pub fn required_bytes(expected_items: usize, false_positive_rate: f64) -> usize {
+    let params = BloomParameters::from_item_count(expected_items, false_positive_rate);
+    params.num_bits.div_ceil(64) * 8
+}
+
+/// Sample the theoretical false positive rate as a filter fills up.
+///
+/// Returns `steps` evenly spaced `(item_count, fpr)` points ending at
+/// `max_items`, computed with [`BloomParameters::calculate_fpr`] without
+/// building a filter. Useful as a plotting dataset for capacity planning.
+///
+/// # Example
+/// ```
+/// use bloom_filter_rs::fpr_curve;
+///
+/// let curve = fpr_curve(9586, 7, 1000, 4);
+/// let counts: Vec<usize> = curve.iter().map(|&(n, _)| n).collect();
+/// assert_eq!(counts, vec![250, 500, 750, 1000]);
+/// ```
+///
+/// # Panics
+/// Panics if `num_bits` or `steps` is 0.
+pub fn fpr_curve(
+    num_bits: usize,
+    num_hashes: usize,
+    max_items: usize,
+    steps: usize,
+) -> Vec<(usize, f64)> {
+    assert!(num_bits > 0, "num_bits must be greater than 0");
+    assert!(steps > 0, "steps must be greater than 0");
+
+    (1..=steps)
+        .map(|step| {
+            let items = max_items * step / steps;
+            (
+                items,
+                BloomParameters::calculate_fpr(num_bits, num_hashes, items),
+            )
+        })
+        .collect()
+}
+
+/// Get the false positive rate that minimizes the combined cost of memory and
+/// false positives, given a memory budget of `num_bits`.
+///
+/// The total cost is modeled as `cost_per_fp * p + cost_per_bit * m`, where
+/// `m = -n * ln(p) / (ln(2)^2)` is the optimal bit count for rate `p`. Here
+/// `cost_per_fp` is the cost of false positives at a rate of 1, i.e. the cost
+/// of one false positive times the number of absent-item queries expected.
+/// Setting the derivative to zero gives
+/// `p = cost_per_bit * n / (cost_per_fp * ln(2)^2)`.
+///
+/// The result is clamped to the lowest rate `num_bits` can reach with optimal
+/// hashing, and to at most 1, which means the filter costs more than the
+/// false positives it prevents.
+///
+/// # Example
+/// ```
+/// use bloom_filter_rs::cost_optimal_fpr;
+///
+/// let cheap = cost_optimal_fpr(100_000, 1000, 1_000.0, 0.01);
+/// let costly = cost_optimal_fpr(100_000, 1000, 100_000.0, 0.01);
+/// assert!(costly < cheap);
+/// ```
+///
+/// # Panics
+/// Panics if `num_bits` or `expected_items` is 0, or if either cost is not
+/// positive.
+pub fn cost_optimal_fpr(
+    num_bits: usize,
+    expected_items: usize,
+    cost_per_fp: f64,
+    cost_per_bit: f64,
+) -> f64 {
+    assert!(num_bits > 0, "num_bits must be greater than 0");
+    assert!(expected_items > 0, "expected_items must be greater than 0");
+    assert!(
+        cost_per_fp > 0.0 && cost_per_bit > 0.0,
+        "costs must be positive"
+    );
+
+    let n = expected_items as f64;
+    let ln_2_sq = f64::ln(2.0).powi(2);
+
+    // Lowest rate reachable within the memory budget
+    let min_fpr = f64::exp(-(num_bits as f64 / n) * ln_2_sq);
+    let optimal = cost_per_bit * n / (cost_per_fp * ln_2_sq);
+
+    optimal.clamp(min_fpr, 1.0)
+}
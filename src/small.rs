@@ -0,0 +1,232 @@
+//! Bloom filter with a compile-time-selected index width.
+//!
+//! Small filters do not need 64-bit indices or 64-bit storage words.
+//! [`SmallBloom`] stores its bits in words of the chosen index type, so a
+//! `u16` filter rounds its size up to 16 bits rather than 64, and derives
+//! every index with adds and conditional subtracts in that type instead of a
+//! per-hash 64-bit modulo. The indices are exactly those of
+//! [`PrecisionBloom`](crate::PrecisionBloom) with the default hashing.
+
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::{Add, BitAnd, BitOr, Sub};
+
+use crate::hash::HashBackend;
+use crate::params::BloomParameters;
+
+/// An unsigned integer type usable as a [`SmallBloom`] bit index and
+/// storage word.
+///
+/// Implemented for `u16`, `u32` and `usize`.
+pub trait IndexWidth:
+    Copy
+    + Debug
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+{
+    /// Number of bits in one storage word of this type.
+    const BITS: usize;
+
+    /// Largest bit count this width can address.
+    ///
+    /// Half the type's range, so adding two in-range indices cannot overflow.
+    const MAX_BITS: usize;
+
+    /// The word with no bits set.
+    const ZERO: Self;
+
+    /// Narrow a value known to be below `MAX_BITS`.
+    fn from_u64(value: u64) -> Self;
+
+    /// Widen the index for addressing the storage words.
+    fn to_usize(self) -> usize;
+
+    /// Get the word with only bit `offset` set.
+    fn bit(offset: usize) -> Self;
+}
+
+macro_rules! impl_index_width {
+    ($($t:ty),*) => {
+        $(
+            impl IndexWidth for $t {
+                const BITS: usize = <$t>::BITS as usize;
+                const MAX_BITS: usize = (<$t>::MAX as usize / 2) + 1;
+                const ZERO: Self = 0;
+
+                #[inline]
+                fn from_u64(value: u64) -> Self {
+                    value as $t
+                }
+
+                #[inline]
+                fn to_usize(self) -> usize {
+                    self as usize
+                }
+
+                #[inline]
+                fn bit(offset: usize) -> Self {
+                    1 << offset
+                }
+            }
+        )*
+    };
+}
+
+impl_index_width!(u16, u32, usize);
+
+/// A bloom filter whose bit storage and index arithmetic use the type `I`.
+///
+/// `SmallBloom<u16>` addresses up to 32768 bits and `SmallBloom<u32>` up to
+/// 2^31; the default `usize` width has no practical limit. Items land on the
+/// same bits as in a [`PrecisionBloom`](crate::PrecisionBloom) with the same
+/// parameters and default hashing, so the two answer identically.
+///
+/// # Example
+/// ```
+/// use bloom_filter_rs::SmallBloom;
+///
+/// let mut filter = SmallBloom::<u16>::with_capacity(500, 0.01);
+/// filter.insert(&"hello");
+///
+/// assert!(filter.contains(&"hello"));
+/// assert!(!filter.contains(&"world"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SmallBloom<I: IndexWidth = usize> {
+    /// Bit storage, least significant bit first within each word
+    words: Vec<I>,
+    /// Filter parameters
+    params: BloomParameters,
+    /// `num_bits` in the index type
+    bound: I,
+    /// `2^64 mod num_bits`, removed whenever the 64-bit double hashing sum
+    /// wraps, to match the wrapping arithmetic of `PrecisionBloom`
+    wrap: I,
+    /// Number of items inserted
+    items_inserted: usize,
+}
+
+impl<I: IndexWidth> SmallBloom<I> {
+    /// Create a new filter with the given parameters.
+    ///
+    /// # Panics
+    /// Panics if the parameters are invalid or `num_bits` exceeds
+    /// [`IndexWidth::MAX_BITS`] for the chosen width.
+    pub fn new(mut params: BloomParameters) -> Self {
+        params.validate().expect("Invalid parameters");
+        params.clamp_hashes();
+        assert!(
+            params.num_bits <= I::MAX_BITS,
+            "num_bits {} exceeds the index width limit of {}",
+            params.num_bits,
+            I::MAX_BITS
+        );
+
+        let num_bits = params.num_bits as u64;
+        Self {
+            words: vec![I::ZERO; params.num_bits.div_ceil(I::BITS)],
+            params,
+            bound: I::from_u64(num_bits),
+            wrap: I::from_u64((u64::MAX % num_bits + 1) % num_bits),
+            items_inserted: 0,
+        }
+    }
+
+    /// Create a new filter sized for the given number of items and false
+    /// positive rate.
+    pub fn with_capacity(expected_items: usize, false_positive_rate: f64) -> Self {
+        Self::new(BloomParameters::from_item_count(
+            expected_items,
+            false_positive_rate,
+        ))
+    }
+
+    /// Generate the item's bit indices in the chosen width.
+    ///
+    /// `PrecisionBloom` reduces the wrapping 64-bit sum `h1 + i * h2`. Here
+    /// both base hashes are reduced once, and each step adds `h2 mod m` in
+    /// the narrow type, removing `2^64 mod m` whenever the 64-bit sum wraps.
+    #[inline]
+    fn indices<T: Hash>(&self, item: &T) -> impl Iterator<Item = I> {
+        let (h1, h2) = HashBackend::AhashSeahash.base_hashes(item);
+        let num_bits = self.params.num_bits as u64;
+
+        let (bound, wrap) = (self.bound, self.wrap);
+        let step = I::from_u64(h2 % num_bits);
+        let mut index = I::from_u64(h1 % num_bits);
+        let mut sum = h1;
+
+        (0..self.params.num_hashes).map(move |_| {
+            let current = index;
+            let (next, wrapped) = sum.overflowing_add(h2);
+            sum = next;
+
+            index = index + step;
+            if index >= bound {
+                index = index - bound;
+            }
+            if wrapped {
+                index = if index >= wrap {
+                    index - wrap
+                } else {
+                    index + (bound - wrap)
+                };
+            }
+            current
+        })
+    }
+
+    /// Split an index into its word position and the mask of its bit.
+    #[inline]
+    fn locate(index: I) -> (usize, I) {
+        let index = index.to_usize();
+        (index / I::BITS, I::bit(index % I::BITS))
+    }
+
+    /// Insert an item into the filter.
+    pub fn insert<T: Hash>(&mut self, item: &T) {
+        self.items_inserted += 1;
+
+        for index in self.indices(item) {
+            let (word, mask) = Self::locate(index);
+            self.words[word] = self.words[word] | mask;
+        }
+    }
+
+    /// Check if an item might be in the filter.
+    pub fn contains<T: Hash>(&self, item: &T) -> bool {
+        self.indices(item).all(|index| {
+            let (word, mask) = Self::locate(index);
+            self.words[word] & mask != I::ZERO
+        })
+    }
+
+    /// Clear all items from the filter.
+    pub fn clear(&mut self) {
+        self.words.fill(I::ZERO);
+        self.items_inserted = 0;
+    }
+
+    /// Get the number of items inserted into the filter.
+    pub fn len(&self) -> usize {
+        self.items_inserted
+    }
+
+    /// Check if the filter is empty (no items inserted).
+    pub fn is_empty(&self) -> bool {
+        self.items_inserted == 0
+    }
+
+    /// Get the heap memory used by the bit storage, in bytes.
+    pub fn memory_usage(&self) -> usize {
+        self.words.len() * std::mem::size_of::<I>()
+    }
+
+    /// Get the parameters of this filter.
+    pub fn parameters(&self) -> &BloomParameters {
+        &self.params
+    }
+}
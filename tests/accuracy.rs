@@ -3,9 +3,14 @@
 //! These tests verify that the bloom filter achieves its target false positive rates
 //! and maintains accuracy across different configurations and workloads.
 
-use bloom_filter_rs::PrecisionBloom;
+use bloom_filter_rs::{
+    aggregate_stats, fpr_validation_sample_size, BitArray, DualBackendBloom, PrecisionBloom,
+};
 use rand::{Rng, SeedableRng};
 
+#[cfg(feature = "rand")]
+use bloom_filter_rs::LeakyBloom;
+
 /// Test that false positive rate is below target for 1% FPR
 #[test]
 fn test_fpr_1_percent() {
@@ -411,3 +416,190 @@ fn test_clear_resets_accuracy() {
         actual_fpr * 100.0
     );
 }
+
+/// Test that bit entropy separates spread and clustered bit patterns
+#[test]
+fn test_bit_entropy() {
+    let mut filter = PrecisionBloom::with_capacity(10_000, 0.01);
+    for i in 0..10_000 {
+        filter.insert(&i);
+    }
+
+    let spread = filter.bit_entropy();
+    println!("Filled filter entropy: {:.4}", spread);
+    assert!(spread > 0.99, "entropy too low: {:.4}", spread);
+
+    // Cluster all set bits into the first few percent of the array
+    let mut clustered = BitArray::new(filter.num_bits());
+    for i in 0..3_000 {
+        clustered.set(i);
+    }
+
+    let low = clustered.region_entropy(64);
+    println!("Clustered entropy: {:.4}", low);
+    assert!(low < 0.3, "entropy too high: {:.4}", low);
+}
+
+/// Test that the sampled FPR estimate tracks the theoretical rate
+#[cfg(feature = "rand")]
+#[test]
+fn test_empirical_fpr_estimate() {
+    let mut filter = PrecisionBloom::with_capacity(10_000, 0.01);
+    for i in 0..10_000 {
+        filter.insert(&i);
+    }
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+    let empirical = filter.empirical_fpr_estimate(50_000, &mut rng);
+    let theoretical = filter.actual_false_positive_rate();
+
+    println!("Empirical FPR: {:.4}%", empirical * 100.0);
+    println!("Theoretical FPR: {:.4}%", theoretical * 100.0);
+
+    assert!(
+        empirical > theoretical * 0.5 && empirical < theoretical * 2.0,
+        "Empirical FPR {:.4}% far from theoretical {:.4}%",
+        empirical * 100.0,
+        theoretical * 100.0
+    );
+}
+
+/// Test that ahash and seahash placements only overlap by chance
+#[test]
+fn test_placement_overlap() {
+    let filter = PrecisionBloom::with_capacity(10_000, 0.01);
+    let items: Vec<u64> = (0..10_000).collect();
+
+    let overlap = filter.placement_overlap(&items);
+    let chance = filter.num_hashes() as f64 / filter.num_bits() as f64;
+
+    println!("Placement overlap: {:.6} (chance: {:.6})", overlap, chance);
+    assert!(overlap < chance * 3.0, "overlap too high: {:.6}", overlap);
+}
+
+/// Test that combining two hash backends cuts the false positive rate
+#[test]
+fn test_dual_backend_fpr() {
+    let mut single = PrecisionBloom::with_capacity(5_000, 0.05);
+    let mut dual = DualBackendBloom::with_capacity(5_000, 0.05);
+
+    for i in 0..5_000 {
+        single.insert(&i);
+        dual.insert(&i);
+    }
+
+    // No false negatives in either filter
+    for i in 0..5_000 {
+        assert!(dual.contains(&i));
+    }
+
+    let test_count = 50_000;
+    let mut single_fps = 0;
+    let mut dual_fps = 0;
+
+    for i in 5_000..(5_000 + test_count) {
+        if single.contains(&i) {
+            single_fps += 1;
+        }
+        if dual.contains(&i) {
+            dual_fps += 1;
+        }
+    }
+
+    let single_fpr = single_fps as f64 / test_count as f64;
+    let dual_fpr = dual_fps as f64 / test_count as f64;
+
+    println!("Single FPR: {:.4}%", single_fpr * 100.0);
+    println!("Dual FPR: {:.4}%", dual_fpr * 100.0);
+
+    assert!(
+        dual_fpr < single_fpr / 5.0,
+        "Dual FPR {:.4}% not substantially below single {:.4}%",
+        dual_fpr * 100.0,
+        single_fpr * 100.0
+    );
+}
+
+/// Test the binomial sample size for FPR validation against hand-computed values
+#[test]
+fn test_fpr_validation_sample_size() {
+    // n = z^2 (1 - p) / (e^2 p), with z = 1.959964 at 95% and 2.575829 at 99%
+    assert_eq!(fpr_validation_sample_size(0.01, 0.1, 0.95), 38_031);
+    assert_eq!(fpr_validation_sample_size(0.001, 0.1, 0.95), 383_762);
+    assert_eq!(fpr_validation_sample_size(0.01, 0.05, 0.95), 152_122);
+    assert_eq!(fpr_validation_sample_size(0.01, 0.1, 0.99), 65_686);
+
+    // Rarer events, tighter error and higher confidence all need more probes
+    let base = fpr_validation_sample_size(0.01, 0.1, 0.95);
+    assert!(fpr_validation_sample_size(0.001, 0.1, 0.95) > base);
+    assert!(fpr_validation_sample_size(0.01, 0.05, 0.95) > base);
+    assert!(fpr_validation_sample_size(0.01, 0.1, 0.99) > base);
+}
+
+/// Test that a leaky filter saves memory in line with its false negative budget
+#[cfg(feature = "rand")]
+#[test]
+fn test_leaky_bloom_fn_budget() {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+    let budget = 0.2;
+    let mut filter = LeakyBloom::with_fn_budget(20_000, 0.01, budget);
+    let classic = PrecisionBloom::with_capacity(20_000, 0.01);
+
+    let saving = 1.0 - filter.memory_usage() as f64 / classic.memory_usage() as f64;
+    println!("Leaky memory saving: {:.2}%", saving * 100.0);
+    assert!(saving > 0.9 * budget, "saving {} too small", saving);
+
+    for i in 0..20_000 {
+        filter.insert(&i, &mut rng);
+    }
+    assert_eq!(filter.len(), 20_000);
+
+    let misses = (0..20_000).filter(|i| !filter.contains(i)).count();
+    let fn_rate = misses as f64 / 20_000.0;
+    println!("Leaky FNR: {:.4}%", fn_rate * 100.0);
+    assert!(
+        (0.9 * budget..1.05 * budget).contains(&fn_rate),
+        "FNR {} far from budget",
+        fn_rate
+    );
+
+    let test_count = 50_000;
+    let false_positives = (20_000..20_000 + test_count)
+        .filter(|i| filter.contains(i))
+        .count();
+    let fpr = false_positives as f64 / test_count as f64;
+    println!("Leaky FPR: {:.4}%", fpr * 100.0);
+    assert!(fpr < 0.015, "FPR {} too high", fpr);
+}
+
+/// Test aggregating metrics across shards
+#[test]
+fn test_aggregate_stats() {
+    let mut shards = vec![PrecisionBloom::with_capacity(1_000, 0.01); 3];
+    for (shard, count) in shards.iter_mut().zip([200, 900, 500]) {
+        for i in 0..count {
+            shard.insert(&i);
+        }
+    }
+
+    let stats = aggregate_stats(&shards);
+    assert_eq!(stats.num_filters, 3);
+    assert_eq!(stats.total_items, 1_600);
+    assert_eq!(stats.fullest, Some(1));
+    assert_eq!(
+        stats.total_memory_bytes,
+        shards.iter().map(|s| s.memory_usage()).sum::<usize>()
+    );
+
+    let fprs: Vec<f64> = shards
+        .iter()
+        .map(|s| s.actual_false_positive_rate())
+        .collect();
+    assert!((stats.routed_fpr - fprs.iter().sum::<f64>() / 3.0).abs() < 1e-12);
+    assert!(stats.broadcast_fpr > stats.routed_fpr);
+    assert!(stats.broadcast_fpr <= fprs.iter().sum::<f64>());
+
+    let empty = aggregate_stats(&[]);
+    assert_eq!(empty.total_items, 0);
+    assert_eq!(empty.fullest, None);
+}
@@ -2,7 +2,15 @@
 //!
 //! These tests verify the fundamental correctness guarantees of the bloom filter.
 
-use bloom_filter_rs::PrecisionBloom;
+#[cfg(feature = "rand")]
+use bloom_filter_rs::AgingBloom;
+use bloom_filter_rs::{
+    cost_optimal_fpr, fpr_curve, params_for_latency, required_bytes, shard_of, BitArray,
+    BloomIndex, BloomParameters, DoubleHashScheme, HashBackend, HybridBloom, LazyBloom,
+    PrecisionBloom, RoundingMode, SmallBloom,
+};
+#[cfg(feature = "rand")]
+use rand::{rngs::StdRng, SeedableRng};
 
 /// Test basic insert and contains operations
 #[test]
@@ -130,6 +138,7 @@ fn test_various_types() {
     assert!(filter.contains(&vec![1, 2, 3]));
 
     // Floats (be careful with floating point comparisons)
+    #[allow(clippy::approx_constant)]
     let float_val: f64 = 3.14;
     filter.insert(&float_val.to_bits());
     assert!(filter.contains(&float_val.to_bits()));
@@ -336,3 +345,833 @@ fn test_overfill_detection() {
     // Now should be overfilled
     assert!(filter.is_overfilled());
 }
+
+/// Test power-of-two sized filters use masking and behave correctly
+#[test]
+fn test_power_of_two_filter() {
+    let params = BloomParameters::from_item_count_pow2(1000, 0.01);
+    assert!(params.num_bits.is_power_of_two());
+    assert!(params.num_bits >= BloomParameters::from_item_count(1000, 0.01).num_bits);
+
+    let mut filter = PrecisionBloom::new(params);
+    for i in 0..1000 {
+        filter.insert(&i);
+    }
+
+    for i in 0..1000 {
+        assert!(filter.contains(&i));
+    }
+
+    // The extra bits should keep the FPR at or below the target
+    assert!(filter.actual_false_positive_rate() <= 0.01);
+}
+
+/// Test canonical key encoding treats equal values of different widths alike
+#[test]
+fn test_bloom_key_canonical_integers() {
+    let mut key_filter = PrecisionBloom::with_capacity(100, 0.01);
+    key_filter.insert_key(&1u32);
+    assert!(key_filter.contains_key(&1u64));
+    assert!(key_filter.contains_key(&1u8));
+    assert!(!key_filter.contains_key(&2u64));
+
+    // The Hash path frames each width differently
+    let mut hash_filter = PrecisionBloom::with_capacity(100, 0.01);
+    hash_filter.insert(&1u32);
+    assert!(hash_filter.contains(&1u32));
+    assert!(!hash_filter.contains(&1u64));
+}
+
+/// Test canonical key encoding for strings and bytes
+#[test]
+fn test_bloom_key_strings_and_bytes() {
+    let mut filter = PrecisionBloom::with_capacity(100, 0.01);
+    filter.insert_key("hello");
+
+    assert!(filter.contains_key(&String::from("hello")));
+    assert!(filter.contains_key(b"hello"));
+    assert!(filter.contains_key(&b"hello".to_vec()));
+    assert!(!filter.contains_key("world"));
+
+    // The encoding is stable, so the key lands on the same bits in every process
+    let positions: Vec<usize> = filter.occupied_ranges().iter().map(|r| r.0).collect();
+    assert_eq!(positions, [35, 190, 282, 437, 592, 684, 839]);
+}
+
+/// Test inserting structured items through their serde encoding
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_items() {
+    #[derive(serde::Serialize)]
+    struct Event {
+        user: String,
+        id: u64,
+        tags: Vec<String>,
+    }
+
+    let event = |id| Event {
+        user: "alice".to_string(),
+        id,
+        tags: vec!["a".to_string(), "b".to_string()],
+    };
+
+    let mut filter = PrecisionBloom::with_capacity(100, 0.01);
+    filter.insert_serde(&event(1)).unwrap();
+
+    // A separately constructed but structurally equal value is found
+    assert!(filter.contains_serde(&event(1)).unwrap());
+    assert!(!filter.contains_serde(&event(2)).unwrap());
+}
+
+/// Test normalized strings match across Unicode compositions
+#[cfg(feature = "unicode")]
+#[test]
+fn test_unicode_normalized_strings() {
+    let composed = "caf\u{e9}";
+    let decomposed = "cafe\u{301}";
+    assert_ne!(composed.as_bytes(), decomposed.as_bytes());
+
+    let mut normalized = PrecisionBloom::with_capacity(100, 0.01);
+    normalized.insert_str_normalized(composed);
+    assert!(normalized.contains_str_normalized(decomposed));
+
+    let mut plain = PrecisionBloom::with_capacity(100, 0.01);
+    plain.insert(&composed);
+    assert!(!plain.contains(&decomposed));
+}
+
+/// Test the metrics snapshot and its JSON field names
+#[cfg(feature = "serde")]
+#[test]
+fn test_metrics_json() {
+    let mut filter = PrecisionBloom::with_capacity(100, 0.01);
+    for i in 0..50 {
+        filter.insert(&i);
+    }
+
+    let metrics = filter.metrics();
+    assert_eq!(metrics.items_inserted, 50);
+    assert_eq!(metrics.num_bits, filter.num_bits());
+    assert_eq!(metrics.memory_bytes, filter.memory_usage());
+    assert!(!metrics.is_overfilled);
+
+    let json = serde_json::to_value(metrics).unwrap();
+    let keys: Vec<&str> = json
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect();
+    for key in [
+        "items_inserted",
+        "num_bits",
+        "num_hashes",
+        "saturation_ratio",
+        "theoretical_fpr",
+        "actual_fpr",
+        "memory_bytes",
+        "is_overfilled",
+    ] {
+        assert!(keys.contains(&key), "missing {}", key);
+    }
+    assert_eq!(json["items_inserted"], 50);
+    assert_eq!(json["num_hashes"], filter.num_hashes());
+    assert_eq!(json["saturation_ratio"], filter.saturation());
+    assert_eq!(json["is_overfilled"], false);
+}
+
+/// Test mapping bit indices to their word storage
+#[test]
+fn test_bit_storage_layout() {
+    assert_eq!(BitArray::locate(0), (0, 0));
+    assert_eq!(BitArray::locate(63), (0, 63));
+    assert_eq!(BitArray::locate(65), (1, 1));
+
+    // The location matches where `set` actually writes
+    let mut bits = BitArray::new(200);
+    bits.set(65);
+    assert_eq!(bits.as_words()[1], 1 << 1);
+
+    let mut filter = PrecisionBloom::with_capacity(100, 0.01);
+    filter.insert(&"item");
+
+    let map = filter.bit_storage_map(&"item");
+    assert_eq!(map.len(), filter.num_hashes());
+    for (word, bit) in map {
+        assert!(word * 64 + bit < filter.num_bits());
+    }
+}
+
+/// Test 128-bit integer keys use a portable encoding
+#[test]
+fn test_u128_keys() {
+    let mut filter = PrecisionBloom::with_capacity(100, 0.01);
+    let id = 0x0123_4567_89ab_cdef_fedc_ba98_7654_3210u128;
+
+    filter.insert_u128(u128::MAX);
+    filter.insert_u128(0);
+    filter.insert_u128(id);
+
+    assert!(filter.contains_u128(u128::MAX));
+    assert!(filter.contains_u128(0));
+    assert!(filter.contains_u128(id));
+    assert!(!filter.contains_u128(id + 1));
+
+    // The key is exactly its little-endian bytes, independent of host endianness
+    assert!(filter.contains_key(&id.to_le_bytes()));
+    assert!(!filter.contains_key(&id.to_be_bytes()));
+
+    // Placement is pinned, so a filter saved by one process loads in another
+    let mut single = PrecisionBloom::with_capacity(100, 0.01);
+    single.insert_u128(id);
+    let positions: Vec<usize> = single.occupied_ranges().iter().map(|r| r.0).collect();
+    assert_eq!(positions, [318, 406, 418, 506, 606, 706, 794]);
+}
+
+/// Test that prefaulting leaves the filter empty and usable
+#[test]
+fn test_prefault() {
+    let mut bits = BitArray::new(1_000_000);
+    bits.prefault();
+    assert_eq!(bits.count_ones(), 0);
+
+    let mut filter = PrecisionBloom::with_capacity(100_000, 0.01);
+    filter.insert(&"before");
+    filter.prefault();
+
+    assert!(filter.contains(&"before"));
+    assert_eq!(filter.len(), 1);
+
+    filter.insert(&"after");
+    assert!(filter.contains(&"after"));
+}
+
+/// Test parameter compatibility depends only on the bit layout
+#[test]
+fn test_parameter_compatibility() {
+    let a = BloomParameters::from_item_count(1000, 0.01);
+    let b = BloomParameters::from_item_count(1000, 0.01);
+    let different_bits = BloomParameters::from_bit_count(a.num_bits + 64, 1000);
+
+    assert!(a.compatible_with(&b));
+    assert!(!a.compatible_with(&different_bits));
+
+    // Only the layout matters, not the sizing intent
+    let relabeled = BloomParameters {
+        expected_items: 1,
+        ..a
+    };
+    assert!(a.compatible_with(&relabeled));
+}
+
+/// Test building a filter sized exactly for a set
+#[test]
+fn test_from_set() {
+    let items: Vec<String> = (0..2_000).map(|i| format!("item_{}", i)).collect();
+    let filter = PrecisionBloom::from_set(&items, 0.01);
+
+    assert_eq!(filter.len(), items.len());
+    assert_eq!(filter.capacity(), items.len());
+    assert!(items.iter().all(|item| filter.contains(item)));
+
+    let empty: Vec<u32> = Vec::new();
+    let filter = PrecisionBloom::from_set(&empty, 0.01);
+    assert!(filter.is_empty());
+    assert_eq!(filter.capacity(), 1);
+}
+
+/// Test hash count rounding modes trade bits for accuracy
+#[test]
+fn test_rounding_modes() {
+    // -log2(0.0118) ≈ 6.4, so the optimal hash count is about 6.4
+    let ceil = BloomParameters::from_item_count_with_rounding(1000, 0.0118, RoundingMode::Ceil);
+    let round = BloomParameters::from_item_count_with_rounding(1000, 0.0118, RoundingMode::Round);
+    let floor = BloomParameters::from_item_count_with_rounding(1000, 0.0118, RoundingMode::Floor);
+
+    assert_eq!(ceil, BloomParameters::from_item_count(1000, 0.0118));
+    assert_eq!(ceil.num_hashes, 7);
+    assert_eq!(round.num_hashes, 6);
+    assert_eq!(floor.num_hashes, 6);
+    assert!(floor.num_bits <= round.num_bits && round.num_bits <= ceil.num_bits);
+
+    for params in [ceil, round, floor] {
+        let mut filter = PrecisionBloom::new(params);
+        for i in 0..1000 {
+            filter.insert(&i);
+        }
+        assert!((0..1000).all(|i| filter.contains(&i)));
+        assert!(filter.actual_false_positive_rate() < 0.013);
+    }
+}
+
+/// Test the index summary rejects absent keys before any shard is queried
+#[test]
+fn test_bloom_index_summary_rejects_absent_keys() {
+    let mut index = BloomIndex::new(16, 100, 0.01);
+    for i in 0..1_000 {
+        index.insert(&i);
+    }
+    assert_eq!(index.len(), 1_000);
+
+    // Present keys pass the summary and touch their shard once each
+    assert!((0..1_000).all(|i| index.contains(&i)));
+    assert_eq!(index.shard_queries(), 1_000);
+
+    // An absent key rejected by the summary never reaches a shard
+    let absent = (1_000..).find(|i| !index.summary().contains(i)).unwrap();
+    assert!(!index.contains(&absent));
+    assert_eq!(index.shard_queries(), 1_000);
+
+    // Each shard holds only the keys routed to it
+    let shard = index.shard_for(&7);
+    assert!(index.shard(shard).contains(&7));
+}
+
+/// Test the item count at which a filter reaches a target false positive rate
+#[test]
+fn test_max_items_for_fpr() {
+    let params = BloomParameters::from_item_count(1000, 0.01);
+
+    // The sizing target is reached at about the expected item count
+    let n = params.max_items_for_fpr(0.01);
+    assert!((950..=1050).contains(&n), "got {}", n);
+    assert!(params.actual_fpr(n) <= 0.01);
+    assert!(params.actual_fpr(n + 1) > 0.01);
+
+    assert!(params.max_items_for_fpr(0.05) > n);
+    assert_eq!(params.max_items_for_fpr(1.0), usize::MAX);
+}
+
+/// Test an optimized filter answers queries exactly like the original
+#[test]
+fn test_optimized_matches_original() {
+    let mut filter = PrecisionBloom::with_capacity(5_000, 0.01);
+    for i in 0..5_000 {
+        filter.insert(&i);
+    }
+
+    let expected: Vec<bool> = (0..20_000).map(|i| filter.contains(&i)).collect();
+    let params = *filter.parameters();
+
+    let optimized = filter.into_optimized();
+    assert_eq!(optimized.len(), 5_000);
+    assert_eq!(optimized.parameters(), &params);
+    for (i, &present) in (0..20_000).zip(&expected) {
+        assert_eq!(optimized.contains(&i), present, "mismatch for {}", i);
+    }
+}
+
+/// Test capping the hash count for latency keeps the false positive target
+#[test]
+fn test_params_for_latency() {
+    let optimal = BloomParameters::from_item_count(1000, 0.01);
+    assert_eq!(optimal.num_hashes, 7);
+
+    let capped = params_for_latency(1000, 0.01, 4);
+    assert_eq!(capped.num_hashes, 4);
+    assert!(capped.num_bits > optimal.num_bits);
+    assert!(capped.actual_fpr(1000) <= 0.01);
+    assert!(BloomParameters::calculate_fpr(capped.num_bits - 1, 4, 1000) > 0.01);
+
+    // A generous budget leaves the optimal parameters alone
+    assert_eq!(params_for_latency(1000, 0.01, 16), optimal);
+}
+
+/// Test parameters round-trip through their config string
+#[test]
+fn test_config_string_round_trip() {
+    for params in [
+        BloomParameters::from_item_count(1000, 0.01),
+        BloomParameters::from_item_count(123_456, 0.0003),
+        BloomParameters::from_bit_count(4096, 300),
+    ] {
+        let config = params.to_config_string();
+        assert_eq!(
+            BloomParameters::from_config_string(&config).unwrap(),
+            params
+        );
+    }
+
+    let reordered = BloomParameters::from_config_string("p=0.01, n=1000, k=7, m=9586").unwrap();
+    assert_eq!(reordered, BloomParameters::from_item_count(1000, 0.01));
+
+    for malformed in [
+        "",
+        "m=9586,k=7,n=1000",
+        "m=9586,k=7,n=1000,p=0.01,m=1",
+        "m=9586,k=7,n=1000,q=0.01",
+        "m=9586,k=seven,n=1000,p=0.01",
+        "m=9586;k=7;n=1000;p=0.01",
+        "m=0,k=7,n=1000,p=0.01",
+        "m=9586,k=7,n=1000,p=1.5",
+    ] {
+        assert!(
+            BloomParameters::from_config_string(malformed).is_err(),
+            "accepted {:?}",
+            malformed
+        );
+    }
+}
+
+/// Test the false positive rate curve rises with the item count
+#[test]
+fn test_fpr_curve() {
+    let curve = fpr_curve(9586, 7, 2000, 50);
+    assert_eq!(curve.len(), 50);
+    assert_eq!(curve[0].0, 40);
+    assert!(curve.windows(2).all(|w| w[0].0 < w[1].0 && w[0].1 < w[1].1));
+
+    let (items, fpr) = *curve.last().unwrap();
+    assert_eq!(items, 2000);
+    assert_eq!(fpr, BloomParameters::calculate_fpr(9586, 7, 2000));
+}
+
+/// Test hash counts above the bit count are clamped
+#[test]
+fn test_excess_hashes_are_clamped() {
+    // Derived parameters never need clamping
+    let mut derived = BloomParameters::from_bit_count(4, 1000);
+    assert!(derived.num_hashes <= derived.num_bits);
+    assert!(!derived.clamp_hashes());
+
+    let pathological = BloomParameters {
+        num_bits: 4,
+        num_hashes: 20,
+        expected_items: 1000,
+        false_positive_rate: 0.5,
+    };
+    let mut clamped = pathological;
+    assert!(clamped.clamp_hashes());
+    assert_eq!(clamped.num_hashes, 4);
+
+    // The clamp shows as fewer hashes than requested
+    let mut filter = PrecisionBloom::new(pathological);
+    assert_eq!(filter.num_hashes(), 4);
+    assert!(filter.num_hashes() < pathological.num_hashes);
+    assert_eq!(filter.parameters().num_hashes, filter.num_hashes());
+    for i in 0..1000 {
+        filter.insert(&i);
+    }
+    assert!(filter.contains(&0));
+}
+
+/// Test counting the candidates the filter reports present
+#[test]
+fn test_count_probable_members() {
+    let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+    for i in 0..1000 {
+        filter.insert(&i);
+    }
+
+    // Half inserted, half absent
+    let candidates: Vec<i32> = (500..1500).collect();
+    let count = filter.count_probable_members(&candidates);
+    assert!((500..=530).contains(&count), "got {}", count);
+
+    assert_eq!(filter.count_probable_members::<i32>(&[]), 0);
+}
+
+/// Test old items fade from an aging filter as it ticks
+#[cfg(feature = "rand")]
+#[test]
+fn test_aging_bloom_fades_old_items() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let mut filter = AgingBloom::with_capacity(1000, 0.01, 0.02);
+    for i in 0..1000 {
+        filter.insert(&i);
+    }
+
+    let present = |filter: &AgingBloom| (0..1000).filter(|i| filter.contains(i)).count();
+    assert_eq!(present(&filter), 1000);
+
+    let mut previous = 1000;
+    for _ in 0..5 {
+        for _ in 0..5 {
+            filter.tick(&mut rng);
+        }
+        let now = present(&filter);
+        assert!(now < previous, "{} not below {}", now, previous);
+        previous = now;
+    }
+    assert!(previous < 100, "{} items survived 25 ticks", previous);
+
+    // Re-inserting refreshes an item
+    filter.insert(&7);
+    assert!(filter.contains(&7));
+}
+
+/// Test every index width gives the same answers as the default filter
+#[test]
+fn test_small_bloom_widths_agree() {
+    for params in [
+        BloomParameters::from_item_count(2_000, 0.01),
+        BloomParameters::from_item_count_pow2(2_000, 0.01),
+        BloomParameters::from_item_count(10, 0.01),
+    ] {
+        let mut default = PrecisionBloom::new(params);
+        let mut wide: SmallBloom = SmallBloom::new(params);
+        let mut narrow = SmallBloom::<u32>::new(params);
+        let mut tiny = SmallBloom::<u16>::new(params);
+
+        let items = params.expected_items as i32;
+        for i in 0..items {
+            default.insert(&i);
+            wide.insert(&i);
+            narrow.insert(&i);
+            tiny.insert(&i);
+        }
+        assert_eq!(narrow.len(), params.expected_items);
+
+        for i in 0..20_000 {
+            let expected = default.contains(&i);
+            assert_eq!(wide.contains(&i), expected, "usize mismatch for {}", i);
+            assert_eq!(narrow.contains(&i), expected, "u32 mismatch for {}", i);
+            assert_eq!(tiny.contains(&i), expected, "u16 mismatch for {}", i);
+            if i < items {
+                assert!(expected);
+            }
+        }
+    }
+
+    // Narrow words round the storage up to fewer padding bits
+    let params = BloomParameters::from_item_count(10, 0.01);
+    assert_eq!(params.num_bits, 96);
+    assert_eq!(PrecisionBloom::new(params).memory_usage(), 16);
+    assert_eq!(SmallBloom::<u16>::new(params).memory_usage(), 12);
+}
+
+/// Test an index width too narrow for the bit count is rejected
+#[test]
+#[should_panic(expected = "index width limit")]
+fn test_small_bloom_rejects_too_many_bits() {
+    SmallBloom::<u16>::with_capacity(10_000, 0.01);
+}
+
+/// Test inserting a range of integers
+#[test]
+fn test_insert_range() {
+    let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+    filter.insert_range(0..1000);
+
+    assert_eq!(filter.len(), 1000);
+    assert!((0..1000u64).all(|i| filter.contains(&i)));
+
+    let mut single = PrecisionBloom::new(BloomParameters::from_bit_count(20_000, 100_000));
+    assert_eq!(single.num_hashes(), 1);
+    single.insert_range(500..600);
+    assert_eq!(single.len(), 100);
+    assert!((500..600u64).all(|i| single.contains(&i)));
+}
+
+/// Test sharded inserts route each item to its hash shard
+#[test]
+fn test_sharded_insert() {
+    let template = PrecisionBloom::with_capacity(1000, 0.01);
+    let mut shards = template.split_by_hash(4);
+    assert_eq!(shards.len(), 4);
+    assert!(shards.iter().all(|shard| shard.is_empty()));
+
+    for i in 0..2000 {
+        let shard = PrecisionBloom::sharded_insert(&mut shards, &i);
+        assert_eq!(shard, shard_of(&i, 4));
+    }
+    assert_eq!(shards.iter().map(|shard| shard.len()).sum::<usize>(), 2000);
+    assert!(shards.iter().all(|shard| !shard.is_empty()));
+
+    let mut union = shards[0].clone();
+    for shard in &shards[1..] {
+        union.union_with(shard).unwrap();
+    }
+    assert!((0..2000).all(|i| union.contains(&i)));
+}
+
+/// Test required bytes matches the memory of a built filter
+#[test]
+fn test_required_bytes() {
+    for (items, fpr) in [(1_000_000, 0.01), (1000, 0.01), (1, 0.5), (77_777, 0.0001)] {
+        let filter = PrecisionBloom::with_capacity(items, fpr);
+        assert_eq!(required_bytes(items, fpr), filter.memory_usage());
+        assert!(required_bytes(items, fpr) * 8 >= filter.num_bits());
+    }
+}
+
+/// Test the SIMD batch query matches scalar contains
+#[test]
+fn test_contains_batch_simd_matches_scalar() {
+    let mut filter = PrecisionBloom::with_capacity(5_000, 0.01);
+    for i in 0..5_000 {
+        filter.insert(&i);
+    }
+
+    // Not a multiple of the lane count, to cover the remainder
+    let queries: Vec<i32> = (0..20_003).collect();
+    let expected: Vec<bool> = queries.iter().map(|i| filter.contains(i)).collect();
+    assert_eq!(filter.contains_batch_simd(&queries), expected);
+
+    let single = PrecisionBloom::new(BloomParameters::from_bit_count(20_000, 100_000));
+    assert_eq!(single.num_hashes(), 1);
+    let expected: Vec<bool> = queries.iter().map(|i| single.contains(i)).collect();
+    assert_eq!(single.contains_batch_simd(&queries), expected);
+
+    assert!(filter.contains_batch_simd::<i32>(&[]).is_empty());
+}
+
+/// Test a hybrid filter answers exactly until it switches to its bloom filter
+#[test]
+fn test_hybrid_bloom_transition() {
+    // A tiny, saturated bloom would report many false positives
+    let mut filter = HybridBloom::new(BloomParameters::from_item_count(8, 0.3), 64);
+    for i in 0..64 {
+        filter.insert(&i);
+        filter.insert(&i);
+    }
+    assert!(filter.is_exact());
+    assert_eq!(filter.len(), 128);
+
+    assert!((0..64).all(|i| filter.contains(&i)));
+    assert_eq!((64..10_000).filter(|i| filter.contains(i)).count(), 0);
+    assert!((64..10_000).any(|i| filter.bloom().contains(&i)));
+
+    filter.insert(&64);
+    assert!(!filter.is_exact());
+    assert!((0..=64).all(|i| filter.contains(&i)));
+    assert!((65..10_000).any(|i| filter.contains(&i)));
+}
+
+/// Test an empty copy keeps the parameters and hash keys
+#[test]
+fn test_empty_like() {
+    let mut original = PrecisionBloom::new(BloomParameters::from_item_count_pow2(1000, 0.01));
+    original.insert(&"hello");
+
+    let copy = PrecisionBloom::empty_like(&original);
+    assert!(copy.is_empty());
+    assert_eq!(copy.saturation(), 0.0);
+    assert_eq!(copy.parameters(), original.parameters());
+    assert!(copy.is_union_compatible(&original));
+
+    let keyed = PrecisionBloom::with_fixed_keys(*original.parameters(), [1; 4], [2; 4]);
+    let keyed_copy = PrecisionBloom::empty_like(&keyed);
+    assert!(keyed_copy.is_union_compatible(&keyed));
+    assert!(!keyed_copy.is_union_compatible(&original));
+
+    // Same keys, so the same bit positions
+    assert_eq!(
+        keyed_copy.insert_preview(&"hello"),
+        keyed.insert_preview(&"hello")
+    );
+}
+
+/// Test merging many filters at once matches chained unions
+#[test]
+fn test_union_many_matches_chained_union() {
+    let template = PrecisionBloom::with_capacity(5_000, 0.01);
+    let mut filters: Vec<PrecisionBloom> = (0..5)
+        .map(|_| PrecisionBloom::empty_like(&template))
+        .collect();
+    for (n, filter) in filters.iter_mut().enumerate() {
+        for i in n * 1000..(n + 1) * 1000 {
+            filter.insert(&i);
+        }
+    }
+
+    let mut chained = filters[0].clone();
+    for filter in &filters[1..] {
+        chained.union_with(filter).unwrap();
+    }
+
+    let merged = PrecisionBloom::union_many(&filters).unwrap();
+    assert_eq!(merged.len(), 5_000);
+    assert_eq!(merged.len(), chained.len());
+    assert_eq!(merged.to_bytes(), chained.to_bytes());
+    assert!((0..5_000usize).all(|i| merged.contains(&i)));
+
+    // Inputs are left untouched
+    assert_eq!(filters[0].len(), 1000);
+
+    filters.push(PrecisionBloom::with_capacity(100, 0.01));
+    assert!(PrecisionBloom::union_many(&filters).is_err());
+}
+
+/// Test querying with a borrowed form of the inserted type
+#[test]
+fn test_contains_borrowed() {
+    let mut filter = PrecisionBloom::with_capacity(1000, 0.01);
+    for i in 0..100 {
+        filter.insert(&format!("key{}", i));
+    }
+    filter.insert(&vec![1u8, 2, 3]);
+
+    assert!(filter.contains_borrowed::<str>("key0"));
+    assert!(filter.contains_borrowed::<str>("key99"));
+    assert!(filter.contains_borrowed::<[u8]>(&[1, 2, 3]));
+
+    let absent = (100..)
+        .map(|i| format!("key{}", i))
+        .find(|key| !filter.contains(key))
+        .unwrap();
+    assert!(!filter.contains_borrowed::<str>(absent.as_str()));
+}
+
+/// Test the share of bits added by rounding up the bit count
+#[test]
+fn test_ceiling_overhead() {
+    let typical = BloomParameters::from_item_count(1000, 0.01).ceiling_overhead();
+    assert!((0.0..0.01).contains(&typical), "got {}", typical);
+
+    // Under one bit of rounding is a large share of a two-bit filter
+    let tiny = BloomParameters::from_item_count(1, 0.5).ceiling_overhead();
+    assert!(tiny > 0.3, "got {}", tiny);
+
+    let pow2 = BloomParameters::from_item_count_pow2(1000, 0.01).ceiling_overhead();
+    assert!(pow2 > typical);
+
+    let floor = BloomParameters::from_item_count_with_rounding(1000, 0.01, RoundingMode::Floor);
+    assert!(floor.ceiling_overhead() <= 0.0);
+}
+
+/// Test building from unique sorted keys matches building from a set
+#[test]
+fn test_from_unique_sorted() {
+    let keys: Vec<String> = (0..2_000).map(|i| format!("key_{:05}", i)).collect();
+    let filter = PrecisionBloom::from_unique_sorted(keys.iter(), 0.01);
+
+    assert_eq!(filter.len(), keys.len());
+    assert_eq!(filter.capacity(), keys.len());
+    assert!(keys.iter().all(|key| filter.contains(key)));
+
+    // Same bits as inserting one by one
+    assert_eq!(filter, PrecisionBloom::from_set(&keys, 0.01));
+
+    let empty = PrecisionBloom::from_unique_sorted(std::iter::empty::<u64>(), 0.01);
+    assert!(empty.is_empty());
+}
+
+/// Test fingerprints depend only on the bits and configuration
+#[test]
+fn test_fingerprint() {
+    let keys: Vec<u64> = (0..500).collect();
+    let a = PrecisionBloom::from_set(&keys, 0.01);
+    let b = PrecisionBloom::from_set(&keys, 0.01);
+    assert_ne!(a.fingerprint(), 0);
+    assert_eq!(a.fingerprint(), b.fingerprint());
+
+    // Pinned so that a change in hashing across processes or releases is noticed
+    assert_eq!(a.fingerprint(), 0x69687d54bb6e5b5efbe1bfb419f6169b);
+
+    // Insert order and tracked counts do not matter
+    let mut reversed = PrecisionBloom::with_capacity(500, 0.01);
+    for key in keys.iter().rev() {
+        reversed.insert(key);
+        reversed.insert(key);
+    }
+    assert_eq!(reversed.fingerprint(), a.fingerprint());
+
+    let other_fpr = PrecisionBloom::from_set(&keys, 0.001);
+    let other_backend =
+        PrecisionBloom::new(*a.parameters()).with_hash_backend(HashBackend::Seahash);
+    let empty = PrecisionBloom::empty_like(&a);
+    assert_ne!(other_fpr.fingerprint(), a.fingerprint());
+    assert_ne!(other_backend.fingerprint(), empty.fingerprint());
+    assert_ne!(empty.fingerprint(), a.fingerprint());
+}
+
+/// Test a lazy filter allocates its bits on the first insert
+#[test]
+fn test_lazy_bloom_allocates_on_first_insert() {
+    let mut filter = LazyBloom::with_capacity(100_000, 0.01);
+    assert!(!filter.is_allocated());
+    assert_eq!(filter.memory_usage(), 0);
+    assert!(filter.is_empty());
+    assert!(!filter.contains(&"hello"));
+
+    filter.insert(&"hello");
+    assert!(filter.is_allocated());
+    assert_eq!(
+        filter.memory_usage(),
+        PrecisionBloom::with_capacity(100_000, 0.01).memory_usage()
+    );
+    assert_eq!(filter.len(), 1);
+    assert!(filter.contains(&"hello"));
+
+    for i in 0..1000 {
+        filter.insert(&i);
+    }
+    assert!((0..1000).all(|i| filter.contains(&i)));
+    assert_eq!(filter.filter().unwrap().parameters(), filter.parameters());
+}
+
+/// Test the cost-optimal rate balances memory against false positive cost
+#[test]
+fn test_cost_optimal_fpr() {
+    let rates: Vec<f64> = [1_000.0, 10_000.0, 100_000.0]
+        .iter()
+        .map(|&cost_per_fp| cost_optimal_fpr(100_000, 1000, cost_per_fp, 0.01))
+        .collect();
+    assert!(
+        rates[0] > rates[1] && rates[1] > rates[2],
+        "got {:?}",
+        rates
+    );
+
+    // The optimum balances the marginal costs: memory saved by a higher rate
+    // equals the extra false positive cost
+    let p = rates[1];
+    let cost = |p: f64| 10_000.0 * p + 0.01 * 1000.0 * -p.ln() / 2f64.ln().powi(2);
+    assert!(cost(p) <= cost(p * 1.1) && cost(p) <= cost(p / 1.1));
+
+    // A tight memory budget caps how low the rate can go
+    let capped = cost_optimal_fpr(1000, 1000, 1e9, 0.01);
+    assert!(capped > rates[2]);
+    assert_eq!(capped, cost_optimal_fpr(1000, 1000, 1e12, 0.01));
+
+    // Bits that cost more than the false positives they prevent
+    assert_eq!(cost_optimal_fpr(100_000, 1000, 1.0, 1.0), 1.0);
+}
+
+/// Test scanning a byte stream for windows the filter contains
+#[test]
+fn test_contains_windows() {
+    let mut filter = PrecisionBloom::with_capacity(1000, 0.0001);
+    filter.insert(&b"GATTACA".as_slice());
+    filter.insert(&b"CATCATG".to_vec());
+
+    let data = b"xxGATTACAyyyyCATCATGzzGATTACA";
+    assert_eq!(filter.contains_windows(data, 7), vec![2, 13, 22]);
+
+    // Other window sizes never match the inserted 7-byte slices here
+    assert!(filter.contains_windows(data, 6).is_empty());
+    assert!(filter.contains_windows(b"GATT", 7).is_empty());
+}
+
+/// Test comparing double hashing schemes on the same items
+#[test]
+fn test_compare_schemes() {
+    let items: Vec<u64> = (0..5_000).collect();
+    let absent: Vec<u64> = (5_000..105_000).collect();
+
+    let results = PrecisionBloom::compare_schemes(&items, &absent, 0.01);
+    let schemes: Vec<DoubleHashScheme> = results.iter().map(|&(scheme, _)| scheme).collect();
+    assert_eq!(schemes, DoubleHashScheme::ALL);
+    for (scheme, fpr) in results {
+        assert!((0.005..0.02).contains(&fpr), "{:?}: {}", scheme, fpr);
+    }
+
+    let no_absent = PrecisionBloom::compare_schemes(&items, &[], 0.01);
+    assert!(no_absent.iter().all(|&(_, fpr)| fpr == 0.0));
+}
+
+/// Test the hash scheme is part of the filter configuration
+#[test]
+fn test_hash_scheme_is_part_of_configuration() {
+    let km = PrecisionBloom::with_capacity(1000, 0.01);
+    let enhanced = PrecisionBloom::empty_like(&km).with_hash_scheme(DoubleHashScheme::Enhanced);
+    assert!(!km.is_union_compatible(&enhanced));
+    assert_eq!(enhanced.provenance().scheme, "enhanced-double-hashing");
+    assert_ne!(km.fingerprint(), enhanced.fingerprint());
+
+    // The scheme survives a backend change and copies
+    let seahash = enhanced.clone().with_hash_backend(HashBackend::Seahash);
+    assert_eq!(seahash.provenance().scheme, "enhanced-double-hashing");
+    assert!(PrecisionBloom::empty_like(&enhanced).is_union_compatible(&enhanced));
+}
@@ -0,0 +1,401 @@
+//! Serialization tests for the bloom filter binary format and archives.
+//!
+//! These tests verify that filters survive persistence with their parameters
+//! and membership intact.
+
+use std::io::Cursor;
+
+use bloom_filter_rs::{
+    BloomArchive, BloomArchiveWriter, BloomError, BloomParameters, DoubleHashScheme,
+    OverfillBehavior, PrecisionBloom,
+};
+
+/// Test that a filter round-trips through the binary format
+#[test]
+fn test_bytes_round_trip() {
+    let mut filter = PrecisionBloom::with_capacity(1_000, 0.01);
+    for i in 0..1_000 {
+        filter.insert(&i);
+    }
+
+    let restored = PrecisionBloom::from_bytes(&filter.to_bytes()).unwrap();
+
+    assert_eq!(restored.parameters(), filter.parameters());
+    assert_eq!(restored.saturation(), filter.saturation());
+    for i in 0..1_000 {
+        assert!(restored.contains(&i));
+    }
+}
+
+/// Test that a filter written by another process loads with every item present
+///
+/// The fixtures were produced by inserting `0..1000u64` into
+/// `with_capacity(1000, 0.01)`. Hashing must not depend on per-process state,
+/// or reloaded filters report false negatives.
+#[test]
+fn test_golden_fixture_loads() {
+    let golden = include_bytes!("fixtures/golden_v4.bin");
+    let legacy = include_bytes!("fixtures/golden_v3.bin");
+
+    for bytes in [&golden[..], &legacy[..]] {
+        let restored = PrecisionBloom::from_bytes(bytes).unwrap();
+        assert_eq!(restored.len(), 1_000);
+        assert!((0..1_000u64).all(|i| restored.contains(&i)));
+    }
+
+    let mut rebuilt = PrecisionBloom::with_capacity(1_000, 0.01);
+    for i in 0..1_000u64 {
+        rebuilt.insert(&i);
+    }
+    assert_eq!(rebuilt.to_bytes(), golden);
+}
+
+/// Test that serialized_size predicts the output of to_bytes
+#[test]
+fn test_serialized_size() {
+    for (items, fpr) in [(1, 0.5), (100, 0.01), (1000, 0.001), (12_345, 0.05)] {
+        let mut filter = PrecisionBloom::with_capacity(items, fpr);
+        for i in 0..items / 2 {
+            filter.insert(&i);
+        }
+        assert_eq!(filter.to_bytes().len(), filter.serialized_size());
+    }
+}
+
+/// Test that a filter round-trips through its occupied bit ranges
+#[test]
+fn test_occupied_ranges_round_trip() {
+    let mut filter = PrecisionBloom::with_capacity(1_000, 0.01);
+    for i in 0..100 {
+        filter.insert(&i);
+    }
+
+    let ranges = filter.occupied_ranges();
+    assert!(ranges.windows(2).all(|pair| pair[0].1 + 1 < pair[1].0));
+
+    let restored = PrecisionBloom::from_ranges(*filter.parameters(), &ranges).unwrap();
+    assert_eq!(restored.occupied_ranges(), ranges);
+    assert_eq!(restored.saturation(), filter.saturation());
+    for i in 0..100 {
+        assert!(restored.contains(&i));
+    }
+
+    let past_end = [(0, filter.num_bits())];
+    assert!(matches!(
+        PrecisionBloom::from_ranges(*filter.parameters(), &past_end),
+        Err(BloomError::InvalidFormat(_))
+    ));
+}
+
+/// Test inserting into a filter file in place
+#[test]
+fn test_insert_to_file() {
+    let path = std::env::temp_dir().join(format!("bloom_insert_{}.bin", std::process::id()));
+
+    let mut filter = PrecisionBloom::with_capacity(1_000, 0.01);
+    filter.insert(&"existing");
+    std::fs::write(&path, filter.to_bytes()).unwrap();
+
+    assert!(PrecisionBloom::insert_to_file(&path, &"appended").unwrap());
+    assert!(!PrecisionBloom::insert_to_file(&path, &"existing").unwrap());
+
+    let reopened = PrecisionBloom::from_bytes(&std::fs::read(&path).unwrap()).unwrap();
+    assert!(reopened.contains(&"existing"));
+    assert!(reopened.contains(&"appended"));
+    assert_eq!(reopened.len(), 3);
+
+    // Matches inserting in memory
+    filter.insert(&"appended");
+    assert_eq!(reopened.occupied_ranges(), filter.occupied_ranges());
+
+    std::fs::write(&path, b"garbage").unwrap();
+    assert!(matches!(
+        PrecisionBloom::insert_to_file(&path, &"x"),
+        Err(BloomError::Io(_)) | Err(BloomError::InvalidFormat(_))
+    ));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// Test that a filter round-trips through base64
+#[cfg(feature = "base64")]
+#[test]
+fn test_base64_round_trip() {
+    let mut filter = PrecisionBloom::with_capacity(50, 0.01);
+    for i in 0..50 {
+        filter.insert(&format!("user_{}", i));
+    }
+
+    let encoded = filter.to_base64();
+    let restored = PrecisionBloom::from_base64(&encoded).unwrap();
+    assert_eq!(restored.len(), 50);
+    for i in 0..50 {
+        assert!(restored.contains(&format!("user_{}", i)));
+    }
+
+    assert!(matches!(
+        PrecisionBloom::from_base64("not base64!"),
+        Err(BloomError::InvalidFormat(_))
+    ));
+    assert!(matches!(
+        PrecisionBloom::from_base64(&encoded[..encoded.len() - 8]),
+        Err(BloomError::InvalidFormat(_))
+    ));
+}
+
+/// Test that malformed input is rejected
+#[test]
+fn test_from_bytes_rejects_garbage() {
+    let filter = PrecisionBloom::with_capacity(100, 0.01);
+    let bytes = filter.to_bytes();
+
+    assert!(matches!(
+        PrecisionBloom::from_bytes(&bytes[..10]),
+        Err(BloomError::InvalidFormat(_))
+    ));
+    assert!(matches!(
+        PrecisionBloom::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(BloomError::InvalidFormat(_))
+    ));
+
+    let mut bad_magic = bytes.clone();
+    bad_magic[0] = b'X';
+    assert!(PrecisionBloom::from_bytes(&bad_magic).is_err());
+}
+
+/// Test that a single corrupted byte is caught by the checksum
+#[test]
+fn test_from_bytes_detects_corruption() {
+    let mut filter = PrecisionBloom::with_capacity(1_000, 0.01);
+    for i in 0..500 {
+        filter.insert(&i);
+    }
+    let bytes = filter.to_bytes();
+
+    for position in [10, 60, bytes.len() / 2, bytes.len() - 1] {
+        let mut corrupted = bytes.clone();
+        corrupted[position] ^= 0x10;
+        assert!(
+            matches!(
+                PrecisionBloom::from_bytes(&corrupted),
+                Err(BloomError::ChecksumMismatch { .. }) | Err(BloomError::InvalidFormat(_))
+            ),
+            "corruption at byte {} was not detected",
+            position
+        );
+    }
+
+    // Corrupting the bit data itself can only be caught by the checksum
+    let mut corrupted = bytes.clone();
+    corrupted[bytes.len() / 2] ^= 0x01;
+    assert!(matches!(
+        PrecisionBloom::from_bytes(&corrupted),
+        Err(BloomError::ChecksumMismatch { .. })
+    ));
+
+    // Version 2 data has no checksum or hashing config and is still readable
+    let mut legacy = [&bytes[..53], &bytes[120..bytes.len() - 4]].concat();
+    legacy[4] = 2;
+    assert_eq!(PrecisionBloom::from_bytes(&legacy).unwrap().len(), 500);
+}
+
+/// Test writing several named filters and loading one by name
+#[test]
+fn test_archive_get_by_name() {
+    let mut sessions = PrecisionBloom::with_capacity(100, 0.01);
+    let mut users = PrecisionBloom::with_capacity(500, 0.001);
+    let mut orders = PrecisionBloom::with_capacity(50, 0.05);
+
+    sessions.insert(&"session-1");
+    for i in 0..500 {
+        users.insert(&format!("user_{}", i));
+    }
+    orders.insert(&42u64);
+
+    let mut writer = BloomArchiveWriter::new();
+    writer.add("sessions", &sessions);
+    writer.add("users", &users);
+    writer.add("orders", &orders);
+    assert_eq!(writer.len(), 3);
+
+    let mut buffer = Vec::new();
+    writer.write_to(&mut buffer).unwrap();
+
+    let mut archive = BloomArchive::open(Cursor::new(buffer)).unwrap();
+    assert_eq!(
+        archive.names().collect::<Vec<_>>(),
+        vec!["sessions", "users", "orders"]
+    );
+
+    let loaded = archive.get("users").unwrap().unwrap();
+    assert_eq!(loaded.parameters(), users.parameters());
+    for i in 0..500 {
+        assert!(loaded.contains(&format!("user_{}", i)));
+    }
+    assert!(!loaded.contains(&"session-1"));
+
+    assert!(archive.get("missing").unwrap().is_none());
+}
+
+/// Test that archives with impossible counts or lengths are rejected
+#[test]
+fn test_archive_rejects_oversized_index() {
+    let mut writer = BloomArchiveWriter::new();
+    writer.add("a", &PrecisionBloom::with_capacity(100, 0.01));
+    let mut buffer = Vec::new();
+    writer.write_to(&mut buffer).unwrap();
+
+    // An entry count far beyond what the data could hold
+    let mut huge_count = buffer.clone();
+    huge_count[5..9].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(matches!(
+        BloomArchive::open(Cursor::new(huge_count)),
+        Err(BloomError::InvalidFormat(_))
+    ));
+
+    // An entry length running past the end of the archive
+    let mut huge_len = buffer.clone();
+    huge_len[20..28].copy_from_slice(&u64::MAX.to_le_bytes());
+    let mut archive = BloomArchive::open(Cursor::new(huge_len)).unwrap();
+    assert!(matches!(
+        archive.get("a"),
+        Err(BloomError::InvalidFormat(_))
+    ));
+
+    let mut archive = BloomArchive::open(Cursor::new(buffer)).unwrap();
+    assert!(archive.get("a").unwrap().is_some());
+}
+
+/// Test that the tracked insert count survives serialization
+#[test]
+fn test_bytes_preserve_item_count() {
+    let mut filter = PrecisionBloom::with_capacity(1_000, 0.01);
+    for i in 0..777 {
+        filter.insert(&i);
+    }
+
+    let restored = PrecisionBloom::from_bytes(&filter.to_bytes()).unwrap();
+
+    assert_eq!(restored.len(), 777);
+    assert_eq!(
+        restored.actual_false_positive_rate(),
+        filter.actual_false_positive_rate()
+    );
+    assert_eq!(restored.status(), filter.status());
+}
+
+/// Test that a filter round-trips through a BitVec with identical bits
+#[cfg(feature = "bitvec")]
+#[test]
+fn test_bitvec_round_trip() {
+    let mut filter = PrecisionBloom::with_capacity(1_000, 0.01);
+    for i in 0..1_000 {
+        filter.insert(&i);
+    }
+
+    let bv = filter.to_bitvec();
+    assert_eq!(bv.len(), filter.num_bits());
+    assert_eq!(
+        bv.count_ones() as f64 / bv.len() as f64,
+        filter.saturation()
+    );
+
+    let restored = PrecisionBloom::from_bitvec(bv.clone(), *filter.parameters());
+    assert_eq!(restored.to_bitvec(), bv);
+    for i in 0..1_000 {
+        assert!(restored.contains(&i));
+    }
+}
+
+/// Test that the hash backend, scheme and overfill behavior survive serialization
+#[test]
+fn test_bytes_preserve_hashing_config() {
+    let params = BloomParameters::from_item_count(500, 0.01);
+    let mut filter = PrecisionBloom::with_fixed_keys(params, [1, 2, 3, 4], [5, 6, 7, 8])
+        .with_hash_scheme(DoubleHashScheme::Enhanced)
+        .with_overfill_behavior(OverfillBehavior::Reject);
+    for i in 0..500 {
+        filter.insert(&i);
+    }
+
+    let restored = PrecisionBloom::from_bytes(&filter.to_bytes()).unwrap();
+
+    assert_eq!(restored, filter);
+    assert_eq!(restored.provenance(), filter.provenance());
+    assert_eq!(restored.overfill_behavior(), OverfillBehavior::Reject);
+    assert!((0..500).all(|i| restored.contains(&i)));
+}
+
+/// Test that an unknown hash backend tag is rejected
+#[test]
+fn test_from_bytes_rejects_unknown_backend() {
+    let mut bytes = PrecisionBloom::with_capacity(100, 0.01).to_bytes();
+    bytes[53] = 0xff;
+    let len = bytes.len();
+    let checksum = crc32fast::hash(&bytes[..len - 4]);
+    bytes[len - 4..].copy_from_slice(&checksum.to_le_bytes());
+
+    assert!(matches!(
+        PrecisionBloom::from_bytes(&bytes),
+        Err(BloomError::InvalidFormat(_))
+    ));
+}
+
+/// Test that inserting into a file honors its stored hashing and overfill behavior
+#[test]
+fn test_insert_to_file_uses_stored_config() {
+    let path = std::env::temp_dir().join(format!("bloom_insert_config_{}.bin", std::process::id()));
+
+    let params = BloomParameters::from_item_count(10, 0.01);
+    let mut filter = PrecisionBloom::with_fixed_keys(params, [9, 8, 7, 6], [5, 4, 3, 2])
+        .with_hash_scheme(DoubleHashScheme::Enhanced)
+        .with_overfill_behavior(OverfillBehavior::Reject);
+    for i in 0..9 {
+        filter.insert(&i);
+    }
+    std::fs::write(&path, filter.to_bytes()).unwrap();
+
+    assert!(PrecisionBloom::insert_to_file(&path, &9).unwrap());
+    filter.insert(&9);
+    let reopened = PrecisionBloom::from_bytes(&std::fs::read(&path).unwrap()).unwrap();
+    assert_eq!(reopened, filter);
+    assert_eq!(reopened.len(), 10);
+
+    // The filter is now at capacity, so the next insert is refused
+    let before = std::fs::read(&path).unwrap();
+    assert!(matches!(
+        PrecisionBloom::insert_to_file(&path, &10),
+        Err(BloomError::CapacityExceeded { capacity: 10 })
+    ));
+    assert_eq!(std::fs::read(&path).unwrap(), before);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// Test that a header with more hashes than bits is rejected
+#[test]
+fn test_from_bytes_rejects_excess_hashes() {
+    let path = std::env::temp_dir().join(format!("bloom_excess_hashes_{}.bin", std::process::id()));
+    let filter = PrecisionBloom::with_capacity(100, 0.01);
+
+    for num_hashes in [filter.num_bits() as u64 + 1, u64::MAX] {
+        let mut bytes = filter.to_bytes();
+        bytes[13..21].copy_from_slice(&num_hashes.to_le_bytes());
+        let len = bytes.len();
+        let checksum = crc32fast::hash(&bytes[..len - 4]);
+        bytes[len - 4..].copy_from_slice(&checksum.to_le_bytes());
+
+        assert!(matches!(
+            PrecisionBloom::from_bytes(&bytes),
+            Err(BloomError::InvalidFormat(_))
+        ));
+
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(matches!(
+            PrecisionBloom::insert_to_file(&path, &"x"),
+            Err(BloomError::InvalidFormat(_))
+        ));
+    }
+
+    std::fs::remove_file(&path).unwrap();
+}